@@ -1,7 +1,11 @@
 use criterion::*;
+use crossbeam_queue::SegQueue;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Mutex;
 use std::thread;
 use wal::doubly;
+use wal::mpsc::{Mpsc, Spsc};
+use wal::queue::Queue;
 use wal::LinkedList;
 
 fn pop_front(c: &mut Criterion) {
@@ -114,7 +118,7 @@ fn push_front(c: &mut Criterion) {
                     for _ in 1..=t {
                         s.spawn(|| {
                             for i in 0..100 {
-                                let _ = list.lock().unwrap().push_front(i);
+                                list.lock().unwrap().push_front(i);
                             }
                         });
                     }
@@ -147,7 +151,7 @@ fn push_front(c: &mut Criterion) {
                     for _ in 1..=t {
                         s.spawn(|| {
                             for i in 0..100 {
-                                let _ = list.push_front(i);
+                                list.push_front(i);
                             }
                         });
                     }
@@ -169,7 +173,7 @@ fn push_back(c: &mut Criterion) {
                     for _ in 1..=t {
                         s.spawn(|| {
                             for i in 0..100 {
-                                let _ = list.lock().unwrap().push_back(i);
+                                list.lock().unwrap().push_back(i);
                             }
                         });
                     }
@@ -209,5 +213,213 @@ fn push_back(c: &mut Criterion) {
     }
 }
 
-criterion_group!(benches, pop_front, pop_back, push_front, push_back);
+fn spsc(c: &mut Criterion) {
+    let mut group = c.benchmark_group("spsc");
+    group.throughput(criterion::Throughput::Elements(1000));
+
+    group.bench_function("wal::doubly", |b| {
+        let list = doubly::LinkedList::new();
+        b.iter(|| {
+            thread::scope(|s| {
+                s.spawn(|| {
+                    for i in 0..1000 {
+                        list.push_back(i);
+                    }
+                });
+                s.spawn(|| {
+                    let mut popped = 0;
+                    while popped < 1000 {
+                        if list.pop_front().is_some() {
+                            popped += 1;
+                        }
+                    }
+                });
+            });
+        });
+    });
+
+    group.bench_function("wal::mpsc::Spsc", |b| {
+        let queue = Spsc::new();
+        b.iter(|| {
+            thread::scope(|s| {
+                s.spawn(|| {
+                    for i in 0..1000 {
+                        unsafe { queue.push_back(i) };
+                    }
+                });
+                s.spawn(|| {
+                    let mut popped = 0;
+                    while popped < 1000 {
+                        if unsafe { queue.pop_front() }.is_some() {
+                            popped += 1;
+                        }
+                    }
+                });
+            });
+        });
+    });
+}
+
+fn mpsc(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mpsc");
+    for t in 1..=4 {
+        group.throughput(criterion::Throughput::Elements(t as u64 * 1000));
+
+        group.bench_with_input(BenchmarkId::new("wal::doubly", t), &t, |b, &t| {
+            let list = doubly::LinkedList::new();
+            b.iter(|| {
+                thread::scope(|s| {
+                    for _ in 1..=t {
+                        s.spawn(|| {
+                            for i in 0..1000 {
+                                list.push_back(i);
+                            }
+                        });
+                    }
+                    s.spawn(|| {
+                        let mut popped = 0;
+                        while popped < t * 1000 {
+                            if list.pop_front().is_some() {
+                                popped += 1;
+                            }
+                        }
+                    });
+                });
+            });
+        });
+
+        group.bench_with_input(BenchmarkId::new("wal::mpsc::Mpsc", t), &t, |b, &t| {
+            let queue = Mpsc::new();
+            b.iter(|| {
+                thread::scope(|s| {
+                    for _ in 1..=t {
+                        s.spawn(|| {
+                            for i in 0..1000 {
+                                queue.push_back(i);
+                            }
+                        });
+                    }
+                    s.spawn(|| {
+                        let mut popped = 0;
+                        while popped < t * 1000 {
+                            if unsafe { queue.pop_front() }.is_some() {
+                                popped += 1;
+                            }
+                        }
+                    });
+                });
+            });
+        });
+    }
+}
+
+/// `t` producers push 1000 elements each while `t` consumers pop them all.
+///
+/// `SegQueue` is the reference point: it batches elements into segments, so
+/// it allocates far less often than a list that allocates a node (and for
+/// `doubly` an anchor too) per push. The single-thread pair mostly measures
+/// that per-element overhead; with more threads contention on the ends takes
+/// over. Consumers yield when they find the queue empty, but with fewer than
+/// `2 * t` cores the numbers still mostly reflect the scheduler.
+fn mpmc(c: &mut Criterion) {
+    fn run(t: usize, push: impl Fn(usize) + Sync, pop: impl Fn() -> bool + Sync) {
+        let popped = AtomicUsize::new(0);
+        thread::scope(|s| {
+            for _ in 0..t {
+                s.spawn(|| {
+                    for i in 0..1000 {
+                        push(i);
+                    }
+                });
+                s.spawn(|| {
+                    while popped.load(Ordering::Relaxed) < t * 1000 {
+                        if pop() {
+                            popped.fetch_add(1, Ordering::Relaxed);
+                        } else {
+                            thread::yield_now();
+                        }
+                    }
+                });
+            }
+        });
+    }
+
+    let mut group = c.benchmark_group("mpmc");
+    for t in 1..=4 {
+        group.throughput(criterion::Throughput::Elements(t as u64 * 1000));
+
+        group.bench_with_input(BenchmarkId::new("crossbeam::SegQueue", t), &t, |b, &t| {
+            let queue = SegQueue::new();
+            b.iter(|| run(t, |i| queue.push(i), || queue.pop().is_some()));
+        });
+
+        group.bench_with_input(BenchmarkId::new("wal::doubly", t), &t, |b, &t| {
+            let list = doubly::LinkedList::new();
+            b.iter(|| run(t, |i| list.push_back(i), || list.pop_front().is_some()));
+        });
+
+        group.bench_with_input(BenchmarkId::new("wal::queue", t), &t, |b, &t| {
+            let queue = Queue::new();
+            b.iter(|| run(t, |i| queue.push_back(i), || queue.pop_front().is_some()));
+        });
+    }
+}
+
+/// Every thread pushes and pops its own elements, so the only state shared
+/// between them is the anchor and the `len` counter. Run it with and without
+/// `--features striped-len` to see what striping the counter buys; the
+/// anchor stays a single contended line either way.
+fn len_contention(c: &mut Criterion) {
+    let mut group = c.benchmark_group("len_contention");
+    for t in [1, 2, 4, 8] {
+        group.throughput(criterion::Throughput::Elements(t as u64 * 2000));
+
+        group.bench_with_input(BenchmarkId::new("wal::doubly", t), &t, |b, &t| {
+            let list = doubly::LinkedList::new();
+            b.iter(|| {
+                thread::scope(|s| {
+                    for _ in 0..t {
+                        s.spawn(|| {
+                            for i in 0..1000 {
+                                list.push_back(i);
+                                black_box(list.pop_back());
+                            }
+                        });
+                    }
+                });
+            });
+        });
+    }
+}
+
+/// `clear` frees every node one after another, dropping elements that
+/// haven't been taken out.
+fn clear(c: &mut Criterion) {
+    fn filled() -> doubly::LinkedList<u64> {
+        let list = doubly::LinkedList::new();
+        for i in 0..10000 {
+            list.push_back(i);
+        }
+        list
+    }
+
+    let mut group = c.benchmark_group("clear");
+    group.throughput(criterion::Throughput::Elements(10000));
+    group.bench_function("u64", |b| {
+        b.iter_batched_ref(filled, |list| list.clear(), BatchSize::LargeInput);
+    });
+}
+
+criterion_group!(
+    benches,
+    pop_front,
+    pop_back,
+    push_front,
+    push_back,
+    spsc,
+    mpsc,
+    mpmc,
+    len_contention,
+    clear
+);
 criterion_main!(benches);
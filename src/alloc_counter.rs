@@ -0,0 +1,110 @@
+//! Counts the bytes each thread has allocated and not freed yet, so tests can
+//! check that a list gives back everything it took, counts reallocations,
+//! and lets a thread make its allocations fail.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::ptr;
+
+struct Counter;
+
+#[global_allocator]
+static ALLOCATOR: Counter = Counter;
+
+thread_local! {
+    static LIVE: Cell<isize> = const { Cell::new(0) };
+    /// How many more allocations succeed before the rest fail, if any do.
+    static FAIL_AFTER: Cell<Option<usize>> = const { Cell::new(None) };
+    static REALLOCS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn failing() -> bool {
+    FAIL_AFTER
+        .try_with(|fail_after| match fail_after.get() {
+            Some(0) => true,
+            Some(n) => {
+                fail_after.set(Some(n - 1));
+                false
+            }
+            None => false,
+        })
+        .unwrap_or(false)
+}
+
+fn record(bytes: isize) {
+    // the slot may already be gone while the thread shuts down
+    let _ = LIVE.try_with(|live| live.set(live.get() + bytes));
+}
+
+unsafe impl GlobalAlloc for Counter {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if failing() {
+            return ptr::null_mut();
+        }
+        record(layout.size() as isize);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        record(-(layout.size() as isize));
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        if failing() {
+            return ptr::null_mut();
+        }
+        record(layout.size() as isize);
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if failing() {
+            return ptr::null_mut();
+        }
+        record(new_size as isize - layout.size() as isize);
+        let _ = REALLOCS.try_with(|reallocs| reallocs.set(reallocs.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+/// Bytes the current thread has allocated and not freed yet.
+pub(crate) fn live_bytes() -> isize {
+    LIVE.with(Cell::get)
+}
+
+/// How many times the current thread has grown or shrunk an allocation.
+pub(crate) fn reallocations() -> usize {
+    REALLOCS.with(Cell::get)
+}
+
+/// Runs `f` and asserts that the current thread freed everything it allocated
+/// in the meantime. Only allocations made on this thread are counted.
+pub(crate) fn assert_no_leak<R>(f: impl FnOnce() -> R) -> R {
+    let before = live_bytes();
+    let result = f();
+    let leaked = live_bytes() - before;
+    assert_eq!(leaked, 0, "{leaked} bytes still allocated");
+    result
+}
+
+/// Runs `f` with every allocation on the current thread failing.
+pub(crate) fn fail_allocations<R>(f: impl FnOnce() -> R) -> R {
+    fail_allocations_after(0, f)
+}
+
+/// Runs `f` with the first `n` allocations on the current thread succeeding
+/// and every one after them failing.
+pub(crate) fn fail_allocations_after<R>(n: usize, f: impl FnOnce() -> R) -> R {
+    struct Restore;
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            FAIL_AFTER.with(|fail_after| fail_after.set(None));
+        }
+    }
+
+    FAIL_AFTER.with(|fail_after| fail_after.set(Some(n)));
+    let _restore = Restore;
+    f()
+}
@@ -1,448 +1,4664 @@
 use seize::{reclaim, AtomicPtr, Collector, Guard, Linked};
-use std::{mem::ManuallyDrop, ptr};
+use std::alloc::{self, Layout};
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::iter::{FusedIterator, Product, Sum};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{SendError, Sender};
+use std::sync::Arc;
 use std::{
-    mem::MaybeUninit,
-    sync::atomic::{AtomicUsize, Ordering},
+    cmp,
+    mem::{self, ManuallyDrop, MaybeUninit},
+    ptr,
 };
 
+use counter::Len;
+use ordering::{ACQUIRE, ACQ_REL, RELAXED, RELEASE};
+
+mod blocking;
+mod counter;
+mod cursor;
+mod iter;
+mod ordering;
+mod snapshot;
+mod view;
+
+pub use blocking::BlockingList;
+pub use cursor::CursorMut;
+pub use iter::{Iter, IterMut};
+pub use snapshot::Snapshot;
+pub use view::DequeView;
+
+/// Lock-free double-ended queue.
+///
+/// Both ends are described by a single immutable `Anchor` that is swapped
+/// with one CAS, following Michael's "CAS-based lock-free algorithm for shared
+/// deques". A push publishes the new end first and repairs the neighbour's
+/// link afterwards (`stabilize`), so links outside of the anchor's `head` and
+/// `tail` are stale and must never be followed.
+///
+/// Pops move elements out of their nodes, so an element must not be borrowed
+/// while another thread could pop it. Pushes, pops and removals work through
+/// `&self`; everything that looks at elements in place takes `&mut self`.
 pub struct LinkedList<T> {
-    head: AtomicPtr<Node<T>>,
-    tail: AtomicPtr<Node<T>>,
-    len: AtomicUsize,
+    anchor: AtomicPtr<Anchor<T>>,
+    len: Len,
+    max_len: AtomicUsize,
+    /// Changes made or attempted so far; see [`version`](Self::version).
+    version: AtomicU64,
     collector: Collector,
+    observer: Option<Box<Observer>>,
+    len_threshold: Option<(usize, Box<LenCallback>)>,
+    _marker: PhantomData<*const T>,
+}
+
+type Observer = dyn Fn(Operation) + Send + Sync;
+
+type LenCallback = dyn Fn(usize) + Send + Sync;
+
+type NodePtr<T> = *mut Linked<Node<T>>;
+
+/// Kind of operation reported to an [observer](LinkedList::observe).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    PushFront,
+    PushBack,
+    PopFront,
+    PopBack,
+    /// An element [removed](LinkedList::remove) through a handle.
+    Remove,
 }
 
+unsafe impl<T: Send> Send for LinkedList<T> {}
+unsafe impl<T: Send + Sync> Sync for LinkedList<T> {}
+
 #[derive(Debug)]
 pub struct Node<T> {
-    inner: MaybeUninit<ManuallyDrop<T>>,
+    inner: ManuallyDrop<T>,
     next: AtomicPtr<Node<T>>,
     prev: AtomicPtr<Node<T>>,
+    /// Set by whoever takes `inner` out. A node [removed](LinkedList::remove)
+    /// through a handle stays linked as a tombstone until a pop reaches it.
+    removed: AtomicBool,
+    /// Set once the node is handed to the collector, to catch a node being
+    /// retired twice, or freed by the list after being retired.
+    #[cfg(debug_assertions)]
+    retired: AtomicBool,
 }
 
 impl<T> Node<T> {
     fn new(t: T) -> Self {
         Self {
-            inner: MaybeUninit::new(ManuallyDrop::new(t)),
+            inner: ManuallyDrop::new(t),
             next: AtomicPtr::new(ptr::null_mut()),
             prev: AtomicPtr::new(ptr::null_mut()),
+            removed: AtomicBool::new(false),
+            #[cfg(debug_assertions)]
+            retired: AtomicBool::new(false),
         }
     }
+
+    fn is_removed(&self) -> bool {
+        self.removed.load(ACQUIRE)
+    }
+
+    /// Claims the element, returning `false` if somebody else already did.
+    fn claim(&self) -> bool {
+        !self.removed.swap(true, ACQ_REL)
+    }
 }
 
-impl<T> LinkedList<T> {
-    pub fn new() -> Self {
-        let list = Self {
-            head: AtomicPtr::new(ptr::null_mut()),
-            tail: AtomicPtr::new(ptr::null_mut()),
-            collector: Collector::new(),
-            len: AtomicUsize::new(0),
-        };
+/// Identifies an element pushed with
+/// [`push_back_return_ref`](LinkedList::push_back_return_ref) or found with
+/// [`find`](LinkedList::find), so it can be [removed](LinkedList::remove)
+/// later wherever it ended up in the list.
+///
+/// The handle lives no longer than the guard it was created with, or the
+/// borrow of the list it was found in, either of which keeps the node from
+/// being reclaimed even once it has been popped.
+pub struct NodeHandle<'g, T> {
+    list: &'g LinkedList<T>,
+    node: *mut Linked<Node<T>>,
+}
 
-        let sentinel = list.collector.link_boxed(Node {
-            inner: MaybeUninit::uninit(),
-            next: AtomicPtr::new(ptr::null_mut()),
-            prev: AtomicPtr::new(ptr::null_mut()),
-        });
+impl<T> NodeHandle<'_, T> {
+    /// Removes the element, like [`LinkedList::remove`]. A handle from
+    /// [`find`](LinkedList::find) keeps the list borrowed, so this is the way
+    /// to use it.
+    pub fn remove(self) -> Option<T> {
+        self.list.take_node(self.node)
+    }
+}
+
+/// The pieces of a list taken apart by [`into_raw`](LinkedList::into_raw),
+/// to be put back together by [`from_raw`](LinkedList::from_raw).
+///
+/// Nothing is freed while a list is in this form; dropping it leaks the
+/// elements.
+pub struct RawList<T> {
+    head: NodePtr<T>,
+    tail: NodePtr<T>,
+    len: usize,
+    max_len: usize,
+    collector: Collector,
+    observer: Option<Box<Observer>>,
+    len_threshold: Option<(usize, Box<LenCallback>)>,
+}
 
-        list.head.store(sentinel, Ordering::Relaxed);
-        list.tail.store(sentinel, Ordering::Relaxed);
+impl<T> RawList<T> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
 
-        list
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
     }
+}
+
+/// The elements of a list cut loose from its collector by
+/// [`detach`](LinkedList::detach), to be sent to another thread and turned
+/// back into a list there with [`into_list`](Detached::into_list).
+///
+/// Unlike [`RawList`], dropping it drops the elements.
+pub struct Detached<T> {
+    chain: Chain<T>,
+    len: usize,
+}
+
+unsafe impl<T: Send> Send for Detached<T> {}
 
+impl<T> Detached<T> {
     pub fn len(&self) -> usize {
-        self.len.load(Ordering::Acquire)
+        self.len
     }
 
-    #[inline]
-    fn push_back_internal(
-        &self,
-        onto: *mut Linked<Node<T>>,
-        new: *mut Linked<Node<T>>,
-        guard: &Guard,
-    ) -> bool {
-        let next = guard.protect(&unsafe { &*onto }.next, Ordering::Acquire);
-        unsafe { &*new }.prev.store(onto, Ordering::Release);
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
 
-        if !next.is_null() {
-            let _ = self
-                .tail
-                .compare_exchange(onto, next, Ordering::Acquire, Ordering::Relaxed);
+    /// Links the nodes into a new list with a collector of its own.
+    pub fn into_list(self) -> LinkedList<T> {
+        let this = ManuallyDrop::new(self);
+        let mut list = LinkedList::new();
+        list.set_ends(this.chain.head, this.chain.tail);
+        *list.len.get_mut() = this.len;
+        *list.max_len.get_mut() = this.len;
+        list
+    }
+}
 
-            false
-        } else {
-            let result = unsafe { &*onto }
-                .next
-                .compare_exchange(ptr::null_mut(), new, Ordering::Release, Ordering::Relaxed)
-                .is_ok();
+struct Anchor<T> {
+    head: *mut Linked<Node<T>>,
+    tail: *mut Linked<Node<T>>,
+    status: Status<T>,
+}
 
-            if result {
-                let _ = self
-                    .tail
-                    .compare_exchange(onto, new, Ordering::Release, Ordering::Relaxed);
-            }
-            result
+impl<T> Anchor<T> {
+    fn empty() -> Self {
+        Self {
+            head: ptr::null_mut(),
+            tail: ptr::null_mut(),
+            status: Status::Stable,
         }
     }
 
-    #[inline]
-    fn push_front_internal(
-        &self,
-        onto: *mut Linked<Node<T>>,
-        new: *mut Linked<Node<T>>,
-        guard: &Guard,
-    ) -> bool {
-        let prev = guard.protect(&unsafe { &*onto }.prev, Ordering::Acquire);
-        unsafe { &*new }.next.store(onto, Ordering::Release);
+    /// Exclusive access to the current anchor, `None` if the list is empty.
+    /// No push can be half way through, so it is always stable here.
+    fn get_mut(anchor: &mut AtomicPtr<Anchor<T>>) -> Option<&mut Self> {
+        let anchor = unsafe { anchor.get_mut().as_mut()? };
+        debug_assert!(anchor.status == Status::Stable);
+        Some(anchor)
+    }
 
-        if !prev.is_null() {
-            let _ = self
-                .head
-                .compare_exchange(onto, prev, Ordering::Acquire, Ordering::Relaxed);
+    fn with_status(&self, status: Status<T>) -> Self {
+        Self {
+            head: self.head,
+            tail: self.tail,
+            status,
+        }
+    }
 
-            false
-        } else {
-            let result = unsafe { &*onto }
-                .prev
-                .compare_exchange(ptr::null_mut(), new, Ordering::Release, Ordering::Relaxed)
-                .is_ok();
-            if result {
-                println!("tail: {}", self.tail.load(Ordering::Acquire) == onto);
-                let _ = self
-                    .head
-                    .compare_exchange(onto, new, Ordering::Release, Ordering::Relaxed);
+    /// Whether the nodes next to both ends link back to them, as they must
+    /// once the anchor is stable. Only meaningful while it is current, which
+    /// a successful swap of it afterwards proves.
+    #[cfg(debug_assertions)]
+    fn ends_linked(&self) -> bool {
+        if self.head == self.tail {
+            return true;
+        }
+        let after_head = unsafe { &*self.head }.next.load(ACQUIRE);
+        let before_tail = unsafe { &*self.tail }.prev.load(ACQUIRE);
+        !after_head.is_null()
+            && !before_tail.is_null()
+            && unsafe { &*after_head }.prev.load(ACQUIRE) == self.head
+            && unsafe { &*before_tail }.next.load(ACQUIRE) == self.tail
+    }
+
+    /// Follows `next` from `node`, bridging the link an unfinished back push
+    /// has not repaired yet. Returns null past the tail.
+    fn next_of(&self, node: *mut Linked<Node<T>>) -> *mut Linked<Node<T>> {
+        if node == self.tail {
+            return ptr::null_mut();
+        }
+        if let Status::PushedBack(first) = self.status {
+            if unsafe { &*first }.prev.load(ACQUIRE) == node {
+                return first;
             }
-            result
         }
+        unsafe { &*node }.next.load(ACQUIRE)
     }
 
-    #[inline]
-    fn pop_front_internal(&self, guard: &Guard) -> Result<Option<T>, ()> {
-        let head = guard.protect(&self.head, Ordering::Acquire);
-        let next = guard.protect(&unsafe { &*head }.next, Ordering::Acquire);
+    /// Follows `prev` from `node`, bridging the link an unfinished front push
+    /// has not repaired yet. Returns null before the head.
+    fn prev_of(&self, node: *mut Linked<Node<T>>) -> *mut Linked<Node<T>> {
+        if node == self.head {
+            return ptr::null_mut();
+        }
+        if self.status == Status::PushedFront && unsafe { &*self.head }.next.load(ACQUIRE) == node {
+            return self.head;
+        }
+        unsafe { &*node }.prev.load(ACQUIRE)
+    }
+}
 
-        if !next.is_null() {
-            match self
-                .head
-                .compare_exchange(head, next, Ordering::Release, Ordering::Relaxed)
-            {
-                Ok(_) => {
-                    let tail = guard.protect(&self.tail, Ordering::Release);
-                    if head == tail {
-                        let _ = self.tail.compare_exchange(
-                            tail,
-                            next,
-                            Ordering::Release,
-                            Ordering::Relaxed,
-                        );
-                    }
+/// Which end, if any, still has a neighbour link waiting to be repaired.
+enum Status<T> {
+    Stable,
+    PushedFront,
+    /// Holds the first of the nodes the push appended, usually just the new
+    /// tail; the node in front of it still has to be linked to it.
+    PushedBack(NodePtr<T>),
+}
 
-                    let data = unsafe { ptr::read(&(*next).inner) };
-                    Ok(unsafe { self.consume_and_retire(head, data) })
-                }
-                Err(_) => Err(()),
-            }
-        } else {
-            Ok(None)
+impl<T> Clone for Status<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Status<T> {}
+
+impl<T> PartialEq for Status<T> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Status::Stable, Status::Stable) | (Status::PushedFront, Status::PushedFront) => true,
+            (Status::PushedBack(a), Status::PushedBack(b)) => a == b,
+            _ => false,
         }
     }
+}
 
-    #[inline]
-    fn pop_back_internal(&self, guard: &Guard) -> Result<Option<T>, ()> {
-        let tail = guard.protect(&self.tail, Ordering::Acquire);
-        let prev = guard.protect(&unsafe { &*tail }.prev, Ordering::Acquire);
+/// Frees a node nobody else can reach, dropping its element unless it was
+/// taken out already.
+unsafe fn free_node<T>(node: NodePtr<T>) {
+    let mut node = Box::from_raw(node);
+    if !*node.removed.get_mut() {
+        ManuallyDrop::drop(&mut node.inner);
+    }
+}
 
-        if !prev.is_null() {
-            match self
-                .tail
-                .compare_exchange(tail, prev, Ordering::Release, Ordering::Relaxed)
-            {
-                Ok(_) => {
-                    let head = guard.protect(&self.head, Ordering::Release);
-                    if head == tail {
-                        let _ = self.head.compare_exchange(
-                            head,
-                            prev,
-                            Ordering::Release,
-                            Ordering::Relaxed,
-                        );
-                    }
+impl<T> LinkedList<T> {
+    pub fn new() -> Self {
+        // Epoch tracking stamps every node with the birth epoch of the
+        // collector that linked it, which would make it unsound to retire a
+        // node through another list's collector. Without it nodes can be
+        // spliced between lists as-is.
+        let collector = Collector::new().epoch_frequency(None);
 
-                    let data = unsafe { ptr::read(&(*prev).inner) };
-                    Ok(unsafe { self.consume_and_retire(tail, data) })
-                }
-                Err(_) => Err(()),
-            }
-        } else {
-            Ok(None)
+        Self {
+            anchor: AtomicPtr::new(ptr::null_mut()),
+            len: Len::new(0),
+            max_len: AtomicUsize::new(0),
+            version: AtomicU64::new(0),
+            collector,
+            observer: None,
+            len_threshold: None,
+            _marker: PhantomData,
         }
     }
 
-    pub fn pop_front(&self) -> Option<T> {
-        let guard = self.collector.enter();
-        loop {
-            if let Ok(head) = self.pop_front_internal(&guard) {
-                return head;
-            }
+    /// Like [`new`](Self::new), but picks the `len` counter for about
+    /// `threads` threads pushing and popping at once. With the `striped-len`
+    /// feature, fewer than 4 threads get a single counter, which is cheaper
+    /// to read. Without the feature the counter is always a single atomic
+    /// and the hint is ignored.
+    pub fn with_concurrency_hint(threads: usize) -> Self {
+        #[cfg(feature = "striped-len")]
+        {
+            let mut list = Self::new();
+            list.len = Len::with_striping(0, threads >= counter::MIN_STRIPED_THREADS);
+            list
+        }
+        #[cfg(not(feature = "striped-len"))]
+        {
+            let _ = threads;
+            Self::new()
         }
     }
 
-    pub fn pop_back(&self) -> Option<T> {
-        let guard = self.collector.enter();
-        loop {
-            if let Ok(tail) = self.pop_back_internal(&guard) {
-                return tail;
-            }
+    /// A list of `n` default elements.
+    pub fn with_defaults(n: usize) -> Self
+    where
+        T: Default,
+    {
+        let mut list = Self::new();
+        for _ in 0..n {
+            let node = list.collector.link_boxed(Node::new(T::default()));
+            list.link_back_node(node);
+        }
+        *list.len.get_mut() = n;
+        *list.max_len.get_mut() = n;
+        list
+    }
+
+    pub fn len(&self) -> usize {
+        let len = self.len.load(ACQUIRE);
+        // briefly below zero while a pop has counted an element that its push
+        // has not counted yet
+        if (len as isize) < 0 {
+            return 0;
         }
+        // pushes don't sum the stripes, so this is where the total is seen
+        #[cfg(feature = "striped-len")]
+        self.raise_max_len(len);
+        len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The largest [`len`](Self::len) any push has left the list with.
+    ///
+    /// With the `striped-len` feature pushes only update their own stripe
+    /// of the counter and never see the total, so this is only the largest
+    /// length seen by `len`, this method, a threshold check or a `&mut self`
+    /// method: a peak in between can be missed.
+    pub fn max_len_observed(&self) -> usize {
+        #[cfg(feature = "striped-len")]
+        self.len();
+        self.max_len.load(RELAXED)
     }
 
+    /// A counter that changes whenever a push, pop or removal changes what
+    /// the list holds, for validating a traversal: if it reads the same
+    /// before and after, the traversal saw the list as it was at one point.
+    /// It may also change without the contents changing, when a push or pop
+    /// has to retry.
+    ///
+    /// Every change through `&self` bumps the counter before it publishes
+    /// itself, so a change the traversal saw is counted by the second read.
+    /// Reading never waits; `&mut self` methods bump the counter directly.
+    pub fn version(&self) -> u64 {
+        self.version.load(ACQUIRE)
+    }
+
+    /// Counts a change through `&self` that is about to be attempted. Comes
+    /// before the change itself, whose release publishes it.
     #[inline]
-    pub fn push_back(&self, t: T) {
-        let guard = self.collector.enter();
-        let new = self.collector.link_boxed(Node::new(t));
-        loop {
-            let tail = guard.protect(&self.tail, Ordering::Acquire);
-            if self.push_back_internal(tail, new, &guard) {
-                self.len.fetch_add(1, Ordering::Release);
-                break;
-            }
-        }
+    fn bump_version(&self) {
+        self.version.fetch_add(1, RELAXED);
     }
 
     #[inline]
-    pub fn push_front(&self, t: T) {
-        let guard = self.collector.enter();
-        let new = self.collector.link_boxed(Node::new(t));
-        loop {
-            let head = guard.protect(&self.head, Ordering::Acquire);
-            if self.push_front_internal(head, new, &guard) {
-                self.len.fetch_add(1, Ordering::Release);
-                break;
-            }
+    fn grow_len(&self) {
+        self.grow_len_by(1);
+    }
+
+    #[inline]
+    fn grow_len_by(&self, n: usize) {
+        // A push publishes its node before counting it, so a pop may uncount
+        // it first and the counter can wrap below zero for a moment.
+        let old = self.len.fetch_add(n, RELEASE);
+        let Some(old) = self.len_total(old, n) else {
+            return;
+        };
+        let len = old.wrapping_add(n);
+        self.check_len_threshold(old, len);
+        if (len as isize) > 0 {
+            self.raise_max_len(len);
         }
     }
 
     #[inline]
-    unsafe fn consume_and_retire(
-        &self,
-        ptr: *mut Linked<Node<T>>,
-        data: MaybeUninit<ManuallyDrop<T>>,
-    ) -> Option<T> {
-        self.collector.retire(ptr, reclaim::boxed::<Node<T>>);
-        self.len.fetch_sub(1, Ordering::Release);
-        return Some(ManuallyDrop::into_inner(data.assume_init()));
+    fn shrink_len(&self) {
+        let old = self.len.fetch_sub(1, RELEASE);
+        let Some(old) = self.len_total(old, 1usize.wrapping_neg()) else {
+            return;
+        };
+        self.check_len_threshold(old, old.wrapping_sub(1));
     }
-}
-//
-// impl<T> Drop for LinkedList<T> {
-//     fn drop(&mut self) {
-//         while self.pop_front().is_some() {}
-//         while self.pop_back().is_some() {}
-//     }
-// }
 
-#[cfg(test)]
-mod tests {
-    use std::{sync::Barrier, thread, time::Duration};
+    /// The total before an update that added `delta` (wrapping), given what
+    /// the update returned. With striping that is only one stripe, and the
+    /// stripes are only summed when a threshold callback needs the total.
+    #[cfg(feature = "striped-len")]
+    #[inline]
+    fn len_total(&self, old: usize, delta: usize) -> Option<usize> {
+        if !self.len.is_striped() {
+            return Some(old);
+        }
+        self.len_threshold.as_ref()?;
+        Some(self.len.load(RELAXED).wrapping_sub(delta))
+    }
 
-    use super::*;
+    #[cfg(not(feature = "striped-len"))]
+    #[inline]
+    fn len_total(&self, old: usize, _delta: usize) -> Option<usize> {
+        Some(old)
+    }
 
-    #[test]
-    fn push_back_pop_front() {
-        let list = LinkedList::new();
-        list.push_back(1);
-        let head = list.head.load(Ordering::Acquire);
-        let data = unsafe { ptr::read(&(*head).inner) };
-        let head_data = unsafe { ManuallyDrop::into_inner(data.assume_init()) };
-
-        let tail = list.tail.load(Ordering::Acquire);
-        let data = unsafe { ptr::read(&(*tail).inner) };
-        let tail_data = unsafe { ManuallyDrop::into_inner(data.assume_init()) };
-        println!("head: {}, tail: {}", head_data, tail_data);
-        list.push_back(2);
+    #[inline]
+    fn raise_max_len(&self, len: usize) {
+        // a plain load first keeps pushes below the high-water mark from
+        // writing to the shared line
+        if len > self.max_len.load(RELAXED) {
+            self.max_len.fetch_max(len, RELAXED);
+        }
+    }
 
-        let head = list.head.load(Ordering::Acquire);
-        let data = unsafe { ptr::read(&(*head).inner) };
-        let head_data = unsafe { ManuallyDrop::into_inner(data.assume_init()) };
+    /// Calls `f` with the new length whenever a push or pop takes `len` from
+    /// below `threshold` to at least `threshold`, or back below it, replacing
+    /// the previous callback.
+    ///
+    /// Each crossing is seen by the one thread whose update of the counter
+    /// made it, and `f` runs on that thread right away, possibly while it
+    /// holds a guard. With the `striped-len` feature the totals a thread sees
+    /// are only approximate, so a crossing may be missed or reported twice.
+    /// Bulk `&mut self` methods such as [`clear`](Self::clear) don't report
+    /// crossings.
+    pub fn on_len_threshold<F>(&mut self, threshold: usize, f: F)
+    where
+        F: Fn(usize) + Send + Sync + 'static,
+    {
+        self.len_threshold = Some((threshold, Box::new(f)));
+    }
 
-        let tail = list.tail.load(Ordering::Acquire);
-        let data = unsafe { ptr::read(&(*tail).inner) };
-        let tail_data = unsafe { ManuallyDrop::into_inner(data.assume_init()) };
-        println!("head: {}, tail: {}", head_data, tail_data);
-        list.push_back(3);
+    #[inline]
+    fn check_len_threshold(&self, old: usize, new: usize) {
+        if let Some((threshold, f)) = &self.len_threshold {
+            let below = |len: usize| (len as isize) < *threshold as isize;
+            if below(old) != below(new) {
+                f(new);
+            }
+        }
+    }
 
-        let head = list.head.load(Ordering::Acquire);
-        let data = unsafe { ptr::read(&(*head).inner) };
-        let head_data = unsafe { ManuallyDrop::into_inner(data.assume_init()) };
-
-        let tail = list.tail.load(Ordering::Acquire);
-        let data = unsafe { ptr::read(&(*tail).inner) };
-        let tail_data = unsafe { ManuallyDrop::into_inner(data.assume_init()) };
-        println!("head: {}, tail: {}", head_data, tail_data);
-
-        // let head = list.tail.load(Ordering::Acquire);
-        // let data = unsafe { ptr::read(&(*head).inner) };
-        // let data = unsafe { ManuallyDrop::into_inner(data.assume_init()) };
-        // assert_eq!(data, 3);
-        //
-        // let head = list.head.load(Ordering::Acquire);
-        // let head_next = unsafe {
-        //     (&*list.head.load(Ordering::Acquire))
-        //         .next
-        //         .load(Ordering::Acquire)
-        // };
-        //
-        // let head_next_2 = unsafe { (*head_next).next.load(Ordering::Acquire) };
-        // let head_next_3 = unsafe { (*head_next_2).next.load(Ordering::Acquire) };
-        //
-        // assert_eq!(unsafe { (*head_next).prev.load(Ordering::Acquire) }, head);
-        // assert_eq!(
-        //     unsafe { (*head_next_2).prev.load(Ordering::Acquire) },
-        //     head_next
-        // );
-        // assert_eq!(
-        //     unsafe { (*head_next_3).prev.load(Ordering::Acquire) },
-        //     head_next_2
-        // );
-        //
-        // assert_eq!(
-        //     unsafe { (*head_next).next.load(Ordering::Acquire) },
-        //     head_next_2
-        // );
-        // assert_eq!(
-        //     unsafe { (*head_next_2).next.load(Ordering::Acquire) },
-        //     head_next_3
-        // );
-        //
-        assert_eq!(list.len(), 3);
-        assert_eq!(list.pop_front().unwrap(), 1);
-        assert_eq!(list.pop_front().unwrap(), 2);
-        assert_eq!(list.pop_front().unwrap(), 3);
-        assert!(list.pop_front().is_none());
-        assert_eq!(list.len(), 0);
+    /// Enters the list's collector. The returned guard is what
+    /// [`count`](Self::count) and
+    /// [`push_back_return_ref`](Self::push_back_return_ref) expect.
+    pub fn guard(&self) -> Guard<'_> {
+        self.collector.enter()
     }
 
-    #[test]
-    fn push_front_pop_front() {
-        let list = LinkedList::new();
+    /// Frees every node and anchor retired so far, instead of waiting for the
+    /// collector to get around to it, for tests that check memory usage.
+    ///
+    /// Dropping a collector reclaims everything it still holds, and with
+    /// `&mut self` no guard can be active, so the collector is simply replaced
+    /// by a fresh one. The list's own nodes don't depend on it.
+    #[cfg(test)]
+    pub(crate) fn force_reclaim(&mut self) {
+        let fresh = self.collector.clone();
+        drop(mem::replace(&mut self.collector, fresh));
+    }
 
-        list.push_front(1);
-        let head = list.head.load(Ordering::Acquire);
-        let data = unsafe { ptr::read(&(*head).inner) };
-        let data = unsafe { ManuallyDrop::into_inner(data.assume_init()) };
-        assert_eq!(data, 1);
+    /// Takes the list apart without freeing anything, e.g. to hand it across
+    /// an FFI boundary as an opaque pointer. Removed elements are unlinked
+    /// first, so the chain holds exactly [`len`](RawList::len) nodes.
+    ///
+    /// The collector goes along with the nodes: it still holds nodes retired
+    /// by earlier pops, and they are only freed once it is dropped.
+    pub fn into_raw(mut self) -> RawList<T> {
+        self.retain(|_| true);
+        let (head, tail) = self
+            .node_ends()
+            .unwrap_or((ptr::null_mut(), ptr::null_mut()));
+        self.set_ends(ptr::null_mut(), ptr::null_mut());
 
-        list.push_front(2);
-        let head = list.head.load(Ordering::Acquire);
-        let data = unsafe { ptr::read(&(*head).inner) };
-        let data = unsafe { ManuallyDrop::into_inner(data.assume_init()) };
-        assert_eq!(data, 2);
+        let mut this = ManuallyDrop::new(self);
+        RawList {
+            head,
+            tail,
+            len: *this.len.get_mut(),
+            max_len: *this.max_len.get_mut(),
+            collector: unsafe { ptr::read(&this.collector) },
+            observer: this.observer.take(),
+            len_threshold: this.len_threshold.take(),
+        }
+    }
 
-        list.push_front(3);
-        let head = list.head.load(Ordering::Acquire);
-        let data = unsafe { ptr::read(&(*head).inner) };
-        let data = unsafe { ManuallyDrop::into_inner(data.assume_init()) };
-        assert_eq!(data, 3);
-
-        let head = list.head.load(Ordering::Acquire);
-        let head_next = unsafe {
-            (&*list.head.load(Ordering::Acquire))
-                .next
-                .load(Ordering::Acquire)
+    /// Takes the nodes out of the list without copying them, leaving the
+    /// collector behind. Removed elements are unlinked first.
+    ///
+    /// Nodes don't depend on the collector that allocated them, so the chain
+    /// can be linked into a new list anywhere. The collector is dropped here,
+    /// which frees every node earlier pops retired through it, so nothing
+    /// that has to be reclaimed travels along.
+    pub fn detach(mut self) -> Detached<T> {
+        self.retain(|_| true);
+        let (head, tail) = self
+            .node_ends()
+            .unwrap_or((ptr::null_mut(), ptr::null_mut()));
+        self.set_ends(ptr::null_mut(), ptr::null_mut());
+        Detached {
+            chain: Chain { head, tail },
+            len: mem::take(self.len.get_mut()),
+        }
+    }
+
+    /// Puts a list taken apart by [`into_raw`](Self::into_raw) back together.
+    ///
+    /// # Safety
+    ///
+    /// `parts` must have come from `into_raw` on a `LinkedList<T>`, and must
+    /// not have been duplicated (say with `ptr::read`) so that the same
+    /// nodes end up in two lists.
+    pub unsafe fn from_raw(parts: RawList<T>) -> Self {
+        let mut list = Self {
+            anchor: AtomicPtr::new(ptr::null_mut()),
+            len: Len::new(parts.len),
+            max_len: AtomicUsize::new(parts.max_len),
+            version: AtomicU64::new(0),
+            collector: parts.collector,
+            observer: parts.observer,
+            len_threshold: parts.len_threshold,
+            _marker: PhantomData,
         };
+        list.set_ends(parts.head, parts.tail);
+        list
+    }
 
-        let head_next_2 = unsafe { (*head_next).next.load(Ordering::Acquire) };
-        let head_next_3 = unsafe { (*head_next_2).next.load(Ordering::Acquire) };
+    /// Iterates front to back over the elements.
+    ///
+    /// `&mut self` keeps anyone from popping them while they are borrowed, so
+    /// no guard is needed.
+    pub fn iter(&mut self) -> Iter<'_, T> {
+        Iter::new(Anchor::get_mut(&mut self.anchor).map(|anchor| &*anchor))
+    }
 
-        assert_eq!(unsafe { (*head_next).prev.load(Ordering::Acquire) }, head);
-        assert_eq!(
-            unsafe { (*head_next_2).prev.load(Ordering::Acquire) },
-            head_next
-        );
-        assert_eq!(
-            unsafe { (*head_next_3).prev.load(Ordering::Acquire) },
-            head_next_2
-        );
+    /// Iterates over every pair of adjacent elements, front to back.
+    pub fn pairs(&mut self) -> impl Iterator<Item = (&T, &T)> {
+        let mut prev = None;
+        self.iter()
+            .filter_map(move |t| prev.replace(t).map(|prev| (prev, t)))
+    }
 
-        assert_eq!(
-            unsafe { (*head_next).next.load(Ordering::Acquire) },
-            head_next_2
-        );
-        assert_eq!(
-            unsafe { (*head_next_2).next.load(Ordering::Acquire) },
-            head_next_3
-        );
+    /// Iterates from the front for as long as `f` holds, stopping at the
+    /// first element it rejects.
+    pub fn iter_while<F>(&mut self, mut f: F) -> impl Iterator<Item = &T>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.iter().take_while(move |t| f(t))
+    }
 
-        assert_eq!(list.len(), 3);
+    /// Iterates over all of `self`, then all of `other`.
+    pub fn chain<'a>(
+        &'a mut self,
+        other: &'a mut LinkedList<T>,
+    ) -> impl DoubleEndedIterator<Item = &'a T> + FusedIterator {
+        self.iter().chain(other.iter())
+    }
 
-        let head_next = unsafe {
-            (&*list.head.load(Ordering::Acquire))
-                .next
-                .load(Ordering::Acquire)
-        };
-        list.pop_front().unwrap();
-        let head = list.head.load(Ordering::Acquire);
-        assert_eq!(head, head_next);
-        assert_eq!(list.pop_front().unwrap(), 3);
-        assert_eq!(list.pop_front().unwrap(), 2);
-        assert_eq!(list.pop_front().unwrap(), 1);
-        assert_eq!(list.len(), 0);
+    /// A cursor on the front element that can move both ways and edit the
+    /// list in place.
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut::new(self)
     }
 
-    #[test]
-    fn push_back_pop_back() {
-        let list = LinkedList::new();
-        list.push_back(1);
-        list.push_back(2);
-        list.push_back(3);
-        assert_eq!(list.len(), 3);
-        assert_eq!(list.pop_back().unwrap(), 3);
-        assert_eq!(list.pop_back().unwrap(), 2);
-        assert_eq!(list.pop_back().unwrap(), 1);
-        assert!(list.pop_back().is_none());
-        assert_eq!(list.len(), 0);
+    /// Plain, `VecDeque`-like access to both ends, for single-threaded use.
+    pub fn deque_view(&mut self) -> DequeView<'_, T> {
+        DequeView::new(self)
     }
 
-    const ITER: u32 = 100;
+    /// Iterates front to back over mutable references to the elements.
+    ///
+    /// `&mut self` rules out concurrent pushes and pops, so no guard is needed.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut::new(Anchor::get_mut(&mut self.anchor))
+    }
 
-    #[test]
-    fn push_pop_multi() {
-        let list = LinkedList::new();
-        let b = Barrier::new(2);
-        thread::scope(|s| {
-            s.spawn(|| {
-                b.wait();
-                for i in 0..ITER {
-                    list.push_front(i);
-                }
-            });
+    /// The front element, mutably. `&mut self` keeps anyone from popping it.
+    pub fn peek_front_mut(&mut self) -> Option<&mut T> {
+        self.iter_mut().next()
+    }
 
-            s.spawn(|| {
-                b.wait();
-                thread::sleep(Duration::from_millis(10));
-                for _ in 0..ITER {
-                    let _ = list.pop_front();
-                }
-            });
-        });
-        assert_eq!(list.len(), 0);
+    /// The back element, mutably. `&mut self` keeps anyone from popping it.
+    pub fn peek_back_mut(&mut self) -> Option<&mut T> {
+        self.iter_mut().next_back()
     }
 
-    #[test]
-    fn push_back_pop_front_temp() {
-        let list = LinkedList::new();
-        list.push_back(1);
-        list.push_back(2);
-        assert_eq!(list.pop_front().unwrap(), 1);
-        assert_eq!(list.pop_front().unwrap(), 2);
+    /// Exchanges the front elements of the two lists, leaving the nodes where
+    /// they are. Does nothing if either list is empty.
+    pub fn swap_front_with(&mut self, other: &mut LinkedList<T>) {
+        if let (Some(a), Some(b)) = (self.peek_front_mut(), other.peek_front_mut()) {
+            mem::swap(a, b);
+        }
     }
 
-    #[test]
-    fn push_front_pop_back_temp() {
-        let list = LinkedList::new();
-        list.push_front(1);
-        list.push_front(2);
-        assert_eq!(list.pop_back().unwrap(), 1);
-        assert_eq!(list.pop_back().unwrap(), 2);
+    /// Puts `value` in place of the element at `index`, returning the old
+    /// one, or `None` (dropping `value`) if the list is shorter than that.
+    /// The node stays where it is.
+    pub fn replace_at(&mut self, index: usize, value: T) -> Option<T> {
+        self.iter_mut().nth(index).map(|t| mem::replace(t, value))
+    }
+
+    /// Counts the elements by walking the list, instead of reading the `len`
+    /// counter, which pushes and pops only update after the fact.
+    ///
+    /// Only the nodes are looked at, never the elements, so this works
+    /// alongside pops.
+    pub fn count(&self, guard: &Guard<'_>) -> usize {
+        self.check_guard(guard);
+        let anchor = guard.protect(&self.anchor, ACQUIRE);
+        let mut iter = Iter::new(unsafe { anchor.as_ref() }.map(|anchor| &**anchor));
+        std::iter::from_fn(|| iter.next_node()).count()
+    }
+
+    /// Overwrites the `len` counter, e.g. with [`count`](Self::count) to
+    /// repair a counter that has drifted. Meant for recovery tooling only.
+    ///
+    /// # Safety
+    ///
+    /// Methods working on `&mut self` trust `len` to match the number of live
+    /// nodes, so `len` must be exactly that before any of them is called
+    /// again.
+    pub unsafe fn set_len(&self, len: usize) {
+        self.len.store(len, RELEASE);
+    }
+
+    /// Returns the element `n` positions from the front, or `None` if the list
+    /// is shorter than that.
+    ///
+    /// Same as `iter().nth(n)`: it walks `n` links, and no element is touched
+    /// beyond checking whether it has been removed.
+    pub fn nth(&mut self, n: usize) -> Option<&T> {
+        self.iter().nth(n)
+    }
+
+    /// The front and back elements. A single element is returned as both.
+    pub fn ends(&mut self) -> (Option<&T>, Option<&T>) {
+        let mut iter = self.iter();
+        let front = iter.next();
+        (front, iter.next_back().or(front))
+    }
+
+    /// Returns the element `rindex` positions from the back, so 0 is the last
+    /// one, or `None` if the list is shorter than that. Walks `prev` links
+    /// from the tail.
+    pub fn rget(&mut self, rindex: usize) -> Option<&T> {
+        self.iter().nth_back(rindex)
+    }
+
+    /// Returns the element at position `index`, counting from the front.
+    ///
+    /// This stands in for `ops::Index`, which would hand out the element
+    /// through `&self`, while another thread may pop it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn index(&mut self, index: usize) -> &T {
+        let len = self.len();
+        match self.nth(index) {
+            Some(t) => t,
+            None => panic!("index out of bounds: the len is {len} but the index is {index}"),
+        }
+    }
+
+    /// Collects references to every element in one traversal, for repeated
+    /// indexed reads.
+    pub fn snapshot(&mut self) -> Snapshot<'_, T> {
+        let mut elements = Vec::with_capacity(self.len());
+        elements.extend(self.iter());
+        Snapshot::new(elements)
+    }
+
+    /// Clones the elements into a `Vec`, front to back, leaving the list as is.
+    pub fn to_vec(&mut self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let mut vec = Vec::with_capacity(self.len());
+        vec.extend(self.iter().cloned());
+        vec
+    }
+
+    /// Turns `self` into a copy of `source`, cloning into the existing nodes
+    /// first so only the difference in length is allocated or freed. The
+    /// observer is not carried over.
+    ///
+    /// This takes the place of `Clone`, which would have to read the elements
+    /// through `&self` while other threads may pop them.
+    pub fn clone_from(&mut self, source: &mut LinkedList<T>)
+    where
+        T: Clone,
+    {
+        let mut elements = source.iter();
+
+        let mut overwritten = 0;
+        for (dst, src) in self.iter_mut().zip(&mut elements) {
+            dst.clone_from(src);
+            overwritten += 1;
+        }
+        while *self.len.get_mut() > overwritten {
+            self.pop_back();
+        }
+        for t in elements {
+            self.push_back(t.clone());
+        }
+    }
+
+    /// Clones every element into its own `Arc`, front to back, so several
+    /// consumers can share the result.
+    pub fn to_arc_vec(&mut self) -> Vec<Arc<T>>
+    where
+        T: Clone,
+    {
+        self.iter().cloned().map(Arc::new).collect()
+    }
+
+    /// Clones the elements `f` returns `true` for into a `Vec`, front to back.
+    pub fn collect_matching<F>(&mut self, mut f: F) -> Vec<T>
+    where
+        T: Clone,
+        F: FnMut(&T) -> bool,
+    {
+        self.iter().filter(|t| f(t)).cloned().collect()
+    }
+
+    /// Iterates over clones of the elements, taken up front, so the iterator
+    /// borrows nothing.
+    pub fn iter_owned(&mut self) -> impl DoubleEndedIterator<Item = T> + ExactSizeIterator
+    where
+        T: Clone,
+    {
+        self.to_vec().into_iter()
+    }
+
+    /// The indices of every element matching `f`, counted from the front, in
+    /// order.
+    pub fn positions<F>(&mut self, mut f: F) -> Vec<usize>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.iter()
+            .enumerate()
+            .filter_map(|(i, t)| f(t).then_some(i))
+            .collect()
+    }
+
+    /// Returns the index, counted from the front, of the last element matching
+    /// `f`. The search runs from the back and stops at the first match.
+    pub fn rposition<F>(&mut self, mut f: F) -> Option<usize>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut iter = self.iter();
+        while let Some(t) = iter.next_back() {
+            if f(t) {
+                return Some(iter.count());
+            }
+        }
+        None
+    }
+
+    /// Searches a sorted list for `value` front to back, stopping at the first
+    /// element that isn't smaller. Returns `Ok` with its index if it is equal,
+    /// or `Err` with the index `value` would have to be inserted at to keep
+    /// the list sorted.
+    ///
+    /// There is no random access, so this is O(n) despite the sorted order.
+    pub fn linear_search(&mut self, value: &T) -> Result<usize, usize>
+    where
+        T: Ord,
+    {
+        let mut index = 0;
+        for t in self.iter() {
+            match t.cmp(value) {
+                cmp::Ordering::Less => index += 1,
+                cmp::Ordering::Equal => return Ok(index),
+                cmp::Ordering::Greater => break,
+            }
+        }
+        Err(index)
+    }
+
+    /// Calls `f` on every element front to back, stopping at the first error.
+    pub fn try_for_each<E, F>(&mut self, f: F) -> Result<(), E>
+    where
+        F: FnMut(&T) -> Result<(), E>,
+    {
+        self.iter().try_for_each(f)
+    }
+
+    /// Calls `f` with the index and a reference of every element, front to
+    /// back.
+    pub fn for_each_indexed<F>(&mut self, mut f: F)
+    where
+        F: FnMut(usize, &T),
+    {
+        for (i, t) in self.iter().enumerate() {
+            f(i, t);
+        }
+    }
+
+    /// The element with the smallest key, the first one if several are
+    /// equally small.
+    pub fn min_by_key<K, F>(&mut self, mut f: F) -> Option<&T>
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.iter().min_by_key(|t| f(t))
+    }
+
+    /// The element with the largest key, the last one if several are equally
+    /// large.
+    pub fn max_by_key<K, F>(&mut self, mut f: F) -> Option<&T>
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.iter().max_by_key(|t| f(t))
+    }
+
+    /// Sums clones of the elements.
+    pub fn sum<S>(&mut self) -> S
+    where
+        T: Clone,
+        S: Sum<T>,
+    {
+        self.iter().cloned().sum()
+    }
+
+    /// Multiplies clones of the elements.
+    pub fn product<P>(&mut self) -> P
+    where
+        T: Clone,
+        P: Product<T>,
+    {
+        self.iter().cloned().product()
+    }
+
+    /// Whether both lists hold the same elements the same number of times,
+    /// in any order.
+    pub fn eq_multiset(&mut self, other: &mut LinkedList<T>) -> bool
+    where
+        T: Eq + Hash,
+    {
+        let mut counts = HashMap::new();
+        for t in self.iter() {
+            *counts.entry(t).or_insert(0isize) += 1;
+        }
+        for t in other.iter() {
+            match counts.get_mut(t) {
+                Some(count) if *count > 0 => *count -= 1,
+                _ => return false,
+            }
+        }
+        counts.values().all(|&count| count == 0)
+    }
+
+    /// How many different values the list holds.
+    pub fn count_distinct(&mut self) -> usize
+    where
+        T: Eq + Hash,
+    {
+        self.iter().collect::<HashSet<_>>().len()
+    }
+
+    /// Whether every element is less than or equal to the one after it.
+    pub fn is_sorted(&mut self) -> bool
+    where
+        T: PartialOrd,
+    {
+        self.pairs().all(|(a, b)| a <= b)
+    }
+
+    /// Builds a new list from what `f` returns for every element, front to
+    /// back, threading `state` through the calls like `Iterator::scan`.
+    pub fn scan<S, B, F>(&mut self, mut state: S, mut f: F) -> LinkedList<B>
+    where
+        F: FnMut(&mut S, &T) -> B,
+    {
+        let scanned = LinkedList::new();
+        for t in self.iter() {
+            scanned.push_back(f(&mut state, t));
+        }
+        scanned
+    }
+
+    /// Folds front to back, stopping at the first `Err`.
+    pub fn try_fold<B, E, F>(&mut self, init: B, f: F) -> Result<B, E>
+    where
+        F: FnMut(B, &T) -> Result<B, E>,
+    {
+        self.iter().try_fold(init, f)
+    }
+
+    fn check_guard(&self, guard: &Guard<'_>) {
+        if let Some(collector) = guard.collector() {
+            assert!(
+                Collector::ptr_eq(collector, &self.collector),
+                "attempted to use a guard from a different collector"
+            );
+        }
+    }
+
+    /// Installs `new` in place of `current`, retiring `current` on success.
+    ///
+    /// An empty list has no anchor at all, so it costs no allocation; `new`
+    /// being empty publishes a null pointer instead. Returns the published
+    /// anchor so the caller can keep stabilizing it.
+    #[inline]
+    fn cas_anchor(
+        &self,
+        current: *mut Linked<Anchor<T>>,
+        new: Anchor<T>,
+    ) -> Option<*mut Linked<Anchor<T>>> {
+        let moved = match unsafe { current.as_ref() } {
+            Some(a) => a.head != new.head || a.tail != new.tail,
+            None => true,
+        };
+        if moved {
+            self.bump_version();
+        }
+        let new = if new.head.is_null() {
+            ptr::null_mut()
+        } else {
+            self.collector.link_boxed(new)
+        };
+        match self.anchor.compare_exchange(current, new, ACQ_REL, ACQUIRE) {
+            Ok(_) => {
+                if !current.is_null() {
+                    unsafe { self.collector.retire(current, reclaim::boxed::<Anchor<T>>) };
+                }
+                Some(new)
+            }
+            Err(_) => {
+                if !new.is_null() {
+                    // never published, nobody else can observe it
+                    let _ = unsafe { Box::from_raw(new) };
+                }
+                None
+            }
+        }
+    }
+
+    /// Repairs the link left behind by an unfinished push and marks the
+    /// anchor stable again.
+    fn stabilize(&self, anchor: *mut Linked<Anchor<T>>) {
+        let a = unsafe { &*anchor };
+        match a.status {
+            Status::Stable => return,
+            Status::PushedBack(first) => {
+                let prev = unsafe { &*first }.prev.load(ACQUIRE);
+                let prev_next = unsafe { &*prev }.next.load(ACQUIRE);
+                if prev_next != first {
+                    if self.anchor.load(ACQUIRE) != anchor {
+                        return;
+                    }
+                    if unsafe { &*prev }
+                        .next
+                        .compare_exchange(prev_next, first, RELEASE, RELAXED)
+                        .is_err()
+                    {
+                        return;
+                    }
+                }
+            }
+            Status::PushedFront => {
+                let next = unsafe { &*a.head }.next.load(ACQUIRE);
+                let next_prev = unsafe { &*next }.prev.load(ACQUIRE);
+                if next_prev != a.head {
+                    if self.anchor.load(ACQUIRE) != anchor {
+                        return;
+                    }
+                    if unsafe { &*next }
+                        .prev
+                        .compare_exchange(next_prev, a.head, RELEASE, RELAXED)
+                        .is_err()
+                    {
+                        return;
+                    }
+                }
+            }
+        }
+        let _ = self.cas_anchor(anchor, a.with_status(Status::Stable));
+    }
+
+    #[inline]
+    fn push_back_internal(&self, onto: *mut Linked<Anchor<T>>, new: *mut Linked<Node<T>>) -> bool {
+        self.push_back_run_internal(onto, new, new)
+    }
+
+    /// Appends the already linked nodes `first..=last` with a single anchor
+    /// swap, so nobody sees only part of them.
+    #[inline]
+    fn push_back_run_internal(
+        &self,
+        onto: *mut Linked<Anchor<T>>,
+        first: NodePtr<T>,
+        last: NodePtr<T>,
+    ) -> bool {
+        if onto.is_null() {
+            return self
+                .cas_anchor(
+                    onto,
+                    Anchor {
+                        head: first,
+                        tail: last,
+                        status: Status::Stable,
+                    },
+                )
+                .is_some();
+        }
+
+        let a = unsafe { &*onto };
+        if a.status != Status::Stable {
+            self.stabilize(onto);
+            return false;
+        }
+        #[cfg(debug_assertions)]
+        let linked = a.ends_linked();
+
+        unsafe { &*first }.prev.store(a.tail, RELEASE);
+        let published = self.cas_anchor(
+            onto,
+            Anchor {
+                head: a.head,
+                tail: last,
+                status: Status::PushedBack(first),
+            },
+        );
+        #[cfg(debug_assertions)]
+        assert!(
+            published.is_none() || linked,
+            "replaced a stable anchor whose ends were not linked up"
+        );
+        match published {
+            Some(anchor) => {
+                self.stabilize(anchor);
+                true
+            }
+            None => false,
+        }
+    }
+
+    #[inline]
+    fn push_front_internal(&self, onto: *mut Linked<Anchor<T>>, new: *mut Linked<Node<T>>) -> bool {
+        if onto.is_null() {
+            return self
+                .cas_anchor(
+                    onto,
+                    Anchor {
+                        head: new,
+                        tail: new,
+                        status: Status::Stable,
+                    },
+                )
+                .is_some();
+        }
+
+        let a = unsafe { &*onto };
+        if a.status != Status::Stable {
+            self.stabilize(onto);
+            return false;
+        }
+        #[cfg(debug_assertions)]
+        let linked = a.ends_linked();
+
+        unsafe { &*new }.next.store(a.head, RELEASE);
+        let published = self.cas_anchor(
+            onto,
+            Anchor {
+                head: new,
+                tail: a.tail,
+                status: Status::PushedFront,
+            },
+        );
+        #[cfg(debug_assertions)]
+        assert!(
+            published.is_none() || linked,
+            "replaced a stable anchor whose ends were not linked up"
+        );
+        match published {
+            Some(anchor) => {
+                self.stabilize(anchor);
+                true
+            }
+            None => false,
+        }
+    }
+
+    #[inline]
+    fn pop_front_internal(&self, guard: &Guard) -> Result<Option<T>, ()> {
+        let anchor = guard.protect(&self.anchor, ACQUIRE);
+        let Some(a) = (unsafe { anchor.as_ref() }) else {
+            return Ok(None);
+        };
+        #[cfg(debug_assertions)]
+        let linked = a.status != Status::Stable || a.ends_linked();
+
+        let next = if a.head == a.tail {
+            Anchor::empty()
+        } else if a.status == Status::Stable {
+            Anchor {
+                head: unsafe { &*a.head }.next.load(ACQUIRE),
+                tail: a.tail,
+                status: Status::Stable,
+            }
+        } else {
+            self.stabilize(anchor);
+            return Err(());
+        };
+
+        let published = self.cas_anchor(anchor, next);
+        #[cfg(debug_assertions)]
+        assert!(
+            published.is_none() || linked,
+            "replaced a stable anchor whose ends were not linked up"
+        );
+        match published {
+            Some(_) if unsafe { &*a.head }.claim() => {
+                Ok(Some(unsafe { self.consume_and_retire(a.head) }))
+            }
+            Some(_) => {
+                unsafe { self.retire_node(a.head) };
+                Err(())
+            }
+            None => Err(()),
+        }
+    }
+
+    #[inline]
+    fn pop_back_internal(&self, guard: &Guard) -> Result<Option<T>, ()> {
+        let anchor = guard.protect(&self.anchor, ACQUIRE);
+        let Some(a) = (unsafe { anchor.as_ref() }) else {
+            return Ok(None);
+        };
+        #[cfg(debug_assertions)]
+        let linked = a.status != Status::Stable || a.ends_linked();
+
+        let prev = if a.head == a.tail {
+            Anchor::empty()
+        } else if a.status == Status::Stable {
+            Anchor {
+                head: a.head,
+                tail: unsafe { &*a.tail }.prev.load(ACQUIRE),
+                status: Status::Stable,
+            }
+        } else {
+            self.stabilize(anchor);
+            return Err(());
+        };
+
+        let published = self.cas_anchor(anchor, prev);
+        #[cfg(debug_assertions)]
+        assert!(
+            published.is_none() || linked,
+            "replaced a stable anchor whose ends were not linked up"
+        );
+        match published {
+            Some(_) if unsafe { &*a.tail }.claim() => {
+                Ok(Some(unsafe { self.consume_and_retire(a.tail) }))
+            }
+            Some(_) => {
+                unsafe { self.retire_node(a.tail) };
+                Err(())
+            }
+            None => Err(()),
+        }
+    }
+
+    pub fn pop_front(&self) -> Option<T> {
+        let head = {
+            let guard = self.collector.enter();
+            loop {
+                if let Ok(head) = self.pop_front_internal(&guard) {
+                    break head;
+                }
+            }
+        };
+        if head.is_some() {
+            self.notify(Operation::PopFront);
+        }
+        head
+    }
+
+    /// Replaces the front element with `new` if it equals `expected`.
+    /// Otherwise gives back a clone of the current front, or `None` if the
+    /// list is empty, and drops `new`.
+    ///
+    /// The comparison looks at the front element in place, which is why this
+    /// takes `&mut self`.
+    pub fn cas_front(&mut self, expected: &T, new: T) -> Result<(), Option<T>>
+    where
+        T: PartialEq + Clone,
+    {
+        match self.peek_front_mut() {
+            Some(front) if *front == *expected => {
+                *front = new;
+                Ok(())
+            }
+            front => Err(front.map(|front| front.clone())),
+        }
+    }
+
+    /// Pops elements from the front for as long as `f` holds, stopping at the
+    /// first element it rejects, which stays in the list.
+    pub fn drain_while<F>(&mut self, mut f: F) -> Vec<T>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut drained = Vec::new();
+        while self.iter().next().is_some_and(&mut f) {
+            drained.extend(self.pop_front());
+        }
+        drained
+    }
+
+    /// Pops elements from the front and hands each to `f`, until `f` returns
+    /// `false` or the list runs empty. Returns how many `f` returned `true`
+    /// for.
+    ///
+    /// `f` decides after taking the element, so the one it stops at has been
+    /// popped too, and is not put back. Use [`drain_while`](Self::drain_while)
+    /// to leave it in the list.
+    pub fn pop_front_while<F>(&self, mut f: F) -> usize
+    where
+        F: FnMut(T) -> bool,
+    {
+        let mut accepted = 0;
+        while let Some(t) = self.pop_front() {
+            if !f(t) {
+                break;
+            }
+            accepted += 1;
+        }
+        accepted
+    }
+
+    /// Pops the front, then keeps merging the new front into it for as long
+    /// as `f` returns a merged value, popping each element that was merged.
+    /// Returns the accumulated value once `f` returns `None` or the list runs
+    /// empty, leaving the element `f` refused at the front.
+    pub fn pop_front_coalesce<F>(&mut self, mut f: F) -> Option<T>
+    where
+        F: FnMut(&T, &T) -> Option<T>,
+    {
+        let mut acc = self.pop_front()?;
+        while let Some(merged) = self.iter().next().and_then(|next| f(&acc, next)) {
+            self.pop_front();
+            acc = merged;
+        }
+        Some(acc)
+    }
+
+    /// Pops up to `max` elements from the front, one per call to `next`.
+    ///
+    /// Nothing is popped ahead of time: dropping the iterator early leaves the
+    /// rest of the list alone.
+    pub fn drain_front(&self, max: usize) -> impl Iterator<Item = T> + '_ {
+        (0..max).map_while(move |_| self.pop_front())
+    }
+
+    /// Empties the list from the front in `Vec`s of `chunk_size` elements,
+    /// the last of which may be shorter. Each chunk is taken when `next` is
+    /// called.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0.
+    pub fn drain_chunks(&mut self, chunk_size: usize) -> impl Iterator<Item = Vec<T>> + '_ {
+        assert!(chunk_size != 0, "chunk size must be non-zero");
+        std::iter::from_fn(move || {
+            let chunk: Vec<T> = self.drain_front(chunk_size).collect();
+            (!chunk.is_empty()).then_some(chunk)
+        })
+    }
+
+    /// Pops up to `out.len()` elements from the front into `out`, returning
+    /// how many were written.
+    ///
+    /// The first `n` slots of `out` are initialized afterwards, where `n` is
+    /// the returned count. Nothing else drops them: it is up to the caller to
+    /// `assume_init` them, and overwriting them leaks the old elements.
+    pub fn pop_front_into_slice(&self, out: &mut [MaybeUninit<T>]) -> usize {
+        for (n, slot) in out.iter_mut().enumerate() {
+            match self.pop_front() {
+                Some(t) => {
+                    slot.write(t);
+                }
+                None => return n,
+            }
+        }
+        out.len()
+    }
+
+    /// Pops elements from the front and sends them through `tx` until the
+    /// list is empty.
+    ///
+    /// Stops early if the receiver is gone, putting the element that could
+    /// not be sent back at the front.
+    pub fn drain_to_channel(&self, tx: Sender<T>) {
+        while let Some(t) = self.pop_front() {
+            if let Err(SendError(t)) = tx.send(t) {
+                self.push_front(t);
+                break;
+            }
+        }
+    }
+
+    pub fn pop_back(&self) -> Option<T> {
+        let tail = {
+            let guard = self.collector.enter();
+            loop {
+                if let Ok(tail) = self.pop_back_internal(&guard) {
+                    break tail;
+                }
+            }
+        };
+        if tail.is_some() {
+            self.notify(Operation::PopBack);
+        }
+        tail
+    }
+
+    /// Pops the front, or returns `make()` if the list is empty.
+    ///
+    /// The new value is pushed and popped right back in one step: it is
+    /// published as the only node of the empty list, already claimed, with a
+    /// single CAS on the anchor. A push that gets there first makes the CAS
+    /// fail and the pop is retried, so `make()` only comes back if the list
+    /// was empty at that point. Of two callers racing on an empty list only
+    /// one inserts at a time, and the other pops again; the claimed node is
+    /// left as a tombstone for the next pop to unlink.
+    pub fn pop_front_or_insert<F>(&self, make: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        let guard = self.collector.enter();
+        let mut make = Some(make);
+        let mut node: NodePtr<T> = ptr::null_mut();
+        loop {
+            match self.pop_front_internal(&guard) {
+                Ok(Some(head)) => {
+                    if !node.is_null() {
+                        // never published, so its element is still ours to drop
+                        unsafe {
+                            *(&mut *node).removed.get_mut() = false;
+                            free_node(node);
+                        }
+                    }
+                    drop(guard);
+                    self.notify(Operation::PopFront);
+                    return head;
+                }
+                Ok(None) => {
+                    if node.is_null() {
+                        let mut claimed = Node::new(make.take().unwrap()());
+                        *claimed.removed.get_mut() = true;
+                        node = self.collector.link_boxed(claimed);
+                    }
+                    let single = Anchor {
+                        head: node,
+                        tail: node,
+                        status: Status::Stable,
+                    };
+                    if self.cas_anchor(ptr::null_mut(), single).is_some() {
+                        // a pop may retire the tombstone right away, but not
+                        // free it while the guard is held
+                        let data = unsafe { ptr::read(&(&*node).inner) };
+                        return ManuallyDrop::into_inner(data);
+                    }
+                }
+                Err(()) => {}
+            }
+        }
+    }
+
+    /// Takes the oldest element, for using the list as a work-stealing deque.
+    ///
+    /// The owning worker pushes and pops its own work at the back, newest
+    /// first, while other workers steal from the front, where the work that
+    /// has waited longest is. Both ends still go through the same anchor, so
+    /// thieves do contend with the owner's CAS; what they avoid is taking the
+    /// work the owner is most likely to want next.
+    pub fn steal(&self) -> Option<T> {
+        self.pop_front()
+    }
+
+    /// Pops up to `max` elements from the back.
+    ///
+    /// The elements are returned in the order they were popped, i.e. back to
+    /// front, so the first element of the `Vec` was the last one in the list.
+    pub fn pop_back_chunk(&self, max: usize) -> Vec<T> {
+        let mut chunk = Vec::with_capacity(max.min(self.len()));
+        while chunk.len() < max {
+            match self.pop_back() {
+                Some(t) => chunk.push(t),
+                None => break,
+            }
+        }
+        chunk
+    }
+
+    #[inline]
+    pub fn push_back(&self, t: T) {
+        {
+            let guard = self.collector.enter();
+            self.push_back_node(&guard, self.collector.link_boxed(Node::new(t)));
+        }
+        self.notify(Operation::PushBack);
+    }
+
+    /// Like [`push_back`](Self::push_back), but returns a handle to the new
+    /// element for a later [`remove`](Self::remove).
+    pub fn push_back_return_ref<'g>(&'g self, t: T, guard: &'g Guard<'_>) -> NodeHandle<'g, T> {
+        self.check_guard(guard);
+        let node = self.push_back_node(guard, self.collector.link_boxed(Node::new(t)));
+        self.notify(Operation::PushBack);
+        NodeHandle { list: self, node }
+    }
+
+    /// Pushes `value` to the back unless an element with an equal key is
+    /// already in the list, returning whether it was pushed.
+    pub fn push_back_unique<K, F>(&mut self, value: T, key_fn: F) -> bool
+    where
+        F: Fn(&T) -> K,
+        K: PartialEq,
+    {
+        let key = key_fn(&value);
+        self.get_or_insert_back(|t| key_fn(t) == key, || value)
+    }
+
+    /// Pushes `make()` to the back unless an element `matches` already,
+    /// returning whether it pushed one. `make` is only called when needed.
+    pub fn get_or_insert_back<M, F>(&mut self, matches: M, make: F) -> bool
+    where
+        M: FnMut(&T) -> bool,
+        F: FnOnce() -> T,
+    {
+        if self.iter().any(matches) {
+            return false;
+        }
+        self.push_back(make());
+        true
+    }
+
+    /// Like [`push_back`](Self::push_back), but gives `t` back instead of
+    /// aborting if the node cannot be allocated. The much smaller anchor each
+    /// push installs is still allocated infallibly.
+    pub fn try_push_back(&self, t: T) -> Result<(), T> {
+        {
+            let guard = self.collector.enter();
+            let new = self.try_link_boxed(t)?;
+            self.push_back_node(&guard, new);
+        }
+        self.notify(Operation::PushBack);
+        Ok(())
+    }
+
+    /// Pushes all of `items` to the back in one go: either every one of them
+    /// ends up in the list, contiguous and in order, or none does. Everything
+    /// is linked up front and published with a single anchor swap, so no
+    /// traversal ever sees part of the batch.
+    ///
+    /// Gives `items` back if a node cannot be allocated.
+    pub fn push_back_atomic_batch(&self, mut items: Vec<T>) -> Result<(), Vec<T>> {
+        let n = items.len();
+        let base = items.as_mut_ptr();
+        // The elements are moved out one by one and put back in the same
+        // buffer on failure, which then needs no allocation.
+        unsafe { items.set_len(0) };
+        let mut run = Chain {
+            head: ptr::null_mut(),
+            tail: ptr::null_mut(),
+        };
+        for i in 0..n {
+            let node = match self.try_link_boxed(unsafe { ptr::read(base.add(i)) }) {
+                Ok(node) => node,
+                Err(t) => {
+                    let mut j = 0;
+                    while let Some(node) = run.pop_front() {
+                        let t = ManuallyDrop::into_inner(Linked::into_inner(*node).inner);
+                        unsafe { base.add(j).write(t) };
+                        j += 1;
+                    }
+                    unsafe {
+                        base.add(i).write(t);
+                        items.set_len(n);
+                    }
+                    return Err(items);
+                }
+            };
+            if run.head.is_null() {
+                run.head = node;
+            } else {
+                unsafe { &*node }.prev.store(run.tail, RELAXED);
+                unsafe { &*run.tail }.next.store(node, RELAXED);
+            }
+            run.tail = node;
+        }
+        if n == 0 {
+            return Ok(());
+        }
+
+        let run = ManuallyDrop::new(run);
+        {
+            let guard = self.collector.enter();
+            loop {
+                let anchor = guard.protect(&self.anchor, ACQUIRE);
+                if self.push_back_run_internal(anchor, run.head, run.tail) {
+                    self.grow_len_by(n);
+                    break;
+                }
+            }
+        }
+        for _ in 0..n {
+            self.notify(Operation::PushBack);
+        }
+        Ok(())
+    }
+
+    /// Fallible `Collector::link_boxed`, allocating the same way `Box` does
+    /// so the node can be freed like any other.
+    fn try_link_boxed(&self, t: T) -> Result<NodePtr<T>, T> {
+        let layout = Layout::new::<Linked<Node<T>>>();
+        let new = unsafe { alloc::alloc(layout) }.cast::<Linked<Node<T>>>();
+        if new.is_null() {
+            return Err(t);
+        }
+        unsafe { new.write(self.collector.link(Node::new(t))) };
+        Ok(new)
+    }
+
+    #[inline]
+    fn push_back_node(&self, guard: &Guard<'_>, new: NodePtr<T>) -> NodePtr<T> {
+        loop {
+            let anchor = guard.protect(&self.anchor, ACQUIRE);
+            if self.push_back_internal(anchor, new) {
+                self.grow_len();
+                return new;
+            }
+        }
+    }
+
+    /// Returns a handle to the first element matching `f`, which can be
+    /// [removed](NodeHandle::remove) through it later. The handle keeps the
+    /// list borrowed.
+    pub fn find<F>(&mut self, mut f: F) -> Option<NodeHandle<'_, T>>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut iter = self.iter();
+        while let Some(node) = iter.next_node() {
+            if f(&unsafe { &*node }.inner) {
+                return Some(NodeHandle { list: self, node });
+            }
+        }
+        None
+    }
+
+    /// Removes the element `handle` refers to, wherever it is in the list.
+    /// Returns `None` if it has already been popped.
+    ///
+    /// The element is taken out right away, but its node stays linked, and
+    /// skipped by everything else, until a pop or a `&mut self` method gets
+    /// rid of it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `handle` was created by a different list.
+    pub fn remove(&self, handle: NodeHandle<'_, T>) -> Option<T> {
+        assert!(
+            ptr::eq(handle.list, self),
+            "attempted to use a handle from a different list"
+        );
+        handle.remove()
+    }
+
+    /// Claims `node` and moves its element out, leaving it as a tombstone.
+    fn take_node(&self, node: NodePtr<T>) -> Option<T> {
+        let node = unsafe { &*node };
+        self.bump_version();
+        if !node.claim() {
+            return None;
+        }
+        let data = unsafe { ptr::read(&node.inner) };
+        self.shrink_len();
+        self.notify(Operation::Remove);
+        Some(ManuallyDrop::into_inner(data))
+    }
+
+    #[inline]
+    pub fn push_front(&self, t: T) {
+        {
+            let guard = self.collector.enter();
+            let new = self.collector.link_boxed(Node::new(t));
+            loop {
+                let anchor = guard.protect(&self.anchor, ACQUIRE);
+                if self.push_front_internal(anchor, new) {
+                    self.grow_len();
+                    break;
+                }
+            }
+        }
+        self.notify(Operation::PushFront);
+    }
+
+    /// Registers a callback invoked after every successful push, pop and
+    /// removal, replacing the previous one.
+    ///
+    /// The callback runs on the thread that performed the operation, after it
+    /// has left the collector, so it is free to use the list itself. A removal
+    /// is the exception: it runs while the caller still holds the guard or
+    /// the borrow its handle came from.
+    pub fn observe<F>(&mut self, f: F)
+    where
+        F: Fn(Operation) + Send + Sync + 'static,
+    {
+        self.observer = Some(Box::new(f));
+    }
+
+    #[inline]
+    fn notify(&self, op: Operation) {
+        if let Some(observer) = &self.observer {
+            observer(op);
+        }
+    }
+
+    /// Moves every element into a freshly allocated node, in order.
+    ///
+    /// Nodes pushed at different times end up scattered across the heap after
+    /// a lot of churn; reallocating them back to back restores some locality
+    /// for traversals. The old nodes are freed immediately instead of going
+    /// through the collector, since `&mut self` guarantees nobody else can
+    /// reach them. Nodes that were already retired by earlier pops are left to
+    /// the collector as usual, and elements removed through a handle are
+    /// dropped from the chain altogether.
+    pub fn compact(&mut self) {
+        let Some(anchor) = Anchor::get_mut(&mut self.anchor) else {
+            return;
+        };
+
+        let mut prev: *mut Linked<Node<T>> = ptr::null_mut();
+        let mut head = ptr::null_mut();
+        let mut current = anchor.head;
+
+        while !current.is_null() {
+            let mut old = unsafe { Box::from_raw(current) };
+            let next = if current == anchor.tail {
+                ptr::null_mut()
+            } else {
+                old.next.load(Ordering::Relaxed)
+            };
+
+            current = next;
+            if *old.removed.get_mut() {
+                continue;
+            }
+
+            let node = Node::new(ManuallyDrop::into_inner(Linked::into_inner(*old).inner));
+            node.prev.store(prev, Ordering::Relaxed);
+            let new = self.collector.link_boxed(node);
+
+            if prev.is_null() {
+                head = new;
+            } else {
+                unsafe { &*prev }.next.store(new, Ordering::Relaxed);
+            }
+
+            prev = new;
+        }
+
+        self.set_ends(head, prev);
+    }
+
+    /// Points every node's `prev` back at the node whose `next` leads to it,
+    /// walking from the head. Meant for recovery tooling, when `next` links
+    /// are known to be intact but `prev` links might not be.
+    pub fn repair_prev_links(&mut self) {
+        let Some((head, tail)) = self.node_ends() else {
+            return;
+        };
+        let mut prev = ptr::null_mut();
+        let mut current = head;
+        loop {
+            let node = unsafe { &*current };
+            node.prev.store(prev, Ordering::Relaxed);
+            if current == tail {
+                break;
+            }
+            prev = current;
+            current = node.next.load(Ordering::Relaxed);
+        }
+    }
+
+    /// Drops every element.
+    ///
+    /// The list is emptied before any element is dropped, so it stays usable
+    /// even if a destructor panics. For types without a destructor, such as
+    /// `Copy` ones, only the nodes are freed.
+    pub fn clear(&mut self) {
+        let Some(anchor) = Anchor::get_mut(&mut self.anchor) else {
+            return;
+        };
+        let chain = Chain {
+            head: anchor.head,
+            tail: anchor.tail,
+        };
+        self.set_ends(ptr::null_mut(), ptr::null_mut());
+        *self.len.get_mut() = 0;
+        drop(chain);
+    }
+
+    /// Exchanges the elements of the two lists in O(1).
+    ///
+    /// Only the contents move; each list keeps its own collector and
+    /// observer. Nodes don't depend on the collector that allocated them, so
+    /// they can be popped and retired through the other list's.
+    pub fn swap(&mut self, other: &mut LinkedList<T>) {
+        mem::swap(self.anchor.get_mut(), other.anchor.get_mut());
+        mem::swap(self.len.get_mut(), other.len.get_mut());
+        for list in [self, other] {
+            *list.version.get_mut() += 1;
+            let len = *list.len.get_mut();
+            let max_len = list.max_len.get_mut();
+            *max_len = (*max_len).max(len);
+        }
+    }
+
+    /// Moves the first element matching `f` to the front, returning whether
+    /// there was one.
+    pub fn move_to_front<F>(&mut self, mut f: F) -> bool
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let Some(node) = self.find_node_mut(|t| f(t)) else {
+            return false;
+        };
+        if Some(node) != self.node_ends().map(|(head, _)| head) {
+            self.unlink_node(node);
+            self.link_front_node(node);
+        }
+        true
+    }
+
+    /// Removes the first element equal to `value`, returning whether there
+    /// was one.
+    pub fn remove_first(&mut self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        let Some(node) = self.find_node_mut(|t| t == value) else {
+            return false;
+        };
+        self.unlink_node(node);
+        *self.len.get_mut() -= 1;
+        let node = Linked::into_inner(*unsafe { Box::from_raw(node) });
+        drop(ManuallyDrop::into_inner(node.inner));
+        true
+    }
+
+    /// Removes every element equal to `value`, returning how many there were.
+    pub fn remove_all(&mut self, value: &T) -> usize
+    where
+        T: PartialEq,
+    {
+        self.retain_counted(|t| t != value)
+    }
+
+    /// Moves every element of `other` to the back of `self` in O(1), leaving
+    /// `other` empty.
+    pub fn append(&mut self, other: &mut LinkedList<T>) {
+        let Some((other_head, other_tail)) = other.node_ends() else {
+            return;
+        };
+        let Some((head, tail)) = self.node_ends() else {
+            self.swap(other);
+            return;
+        };
+
+        unsafe { &*tail }.next.store(other_head, Ordering::Relaxed);
+        unsafe { &*other_head }.prev.store(tail, Ordering::Relaxed);
+        self.set_ends(head, other_tail);
+        other.set_ends(ptr::null_mut(), ptr::null_mut());
+
+        let len = self.len.get_mut();
+        *len += mem::take(other.len.get_mut());
+        let max_len = self.max_len.get_mut();
+        *max_len = (*max_len).max(*len);
+    }
+
+    /// Moves every element of `other` to the front of `self` in O(1), in
+    /// order, leaving `other` empty.
+    pub fn prepend_list(&mut self, other: &mut LinkedList<T>) {
+        let Some((other_head, other_tail)) = other.node_ends() else {
+            return;
+        };
+        let Some((head, tail)) = self.node_ends() else {
+            self.swap(other);
+            return;
+        };
+
+        unsafe { &*other_tail }.next.store(head, Ordering::Relaxed);
+        unsafe { &*head }.prev.store(other_tail, Ordering::Relaxed);
+        self.set_ends(other_head, tail);
+        other.set_ends(ptr::null_mut(), ptr::null_mut());
+
+        let len = self.len.get_mut();
+        *len += mem::take(other.len.get_mut());
+        let max_len = self.max_len.get_mut();
+        *max_len = (*max_len).max(*len);
+    }
+
+    /// Moves up to `n` elements from the back of `other` to the front of
+    /// `self`, keeping their order. Only the links at the cut are touched.
+    pub fn transfer_back_to_front(&mut self, other: &mut LinkedList<T>, n: usize) {
+        let keep = other.len.get_mut().saturating_sub(n);
+        let mut back = other.take_front(keep);
+        other.swap(&mut back);
+        self.prepend_list(&mut back);
+    }
+
+    /// Merges two lists by taking elements from `a` and `b` in turn, starting
+    /// with `a`. Whatever is left of the longer one goes at the end.
+    pub fn interleave(mut a: LinkedList<T>, mut b: LinkedList<T>) -> LinkedList<T> {
+        let mut merged = LinkedList::new();
+        loop {
+            let Some(node) = a.unlink_front_node() else {
+                merged.append(&mut b);
+                break;
+            };
+            merged.link_back_node(node);
+            merged.grow_len();
+            mem::swap(&mut a, &mut b);
+        }
+        merged
+    }
+
+    /// Pairs up the elements of both lists front to back, stopping at the end
+    /// of the shorter one. The rest of the longer one is dropped.
+    pub fn zip<U>(mut self, mut other: LinkedList<U>) -> LinkedList<(T, U)> {
+        let zipped = LinkedList::new();
+        let pairs = std::iter::from_fn(|| self.pop_front_mut())
+            .zip(std::iter::from_fn(|| other.pop_front_mut()));
+        for pair in pairs {
+            zipped.push_back(pair);
+        }
+        zipped
+    }
+
+    /// Moves the elements into a `Vec`, front to back. The `Vec` is sized
+    /// for [`len`](Self::len) up front, so it never grows while draining.
+    pub fn into_vec(mut self) -> Vec<T> {
+        let mut vec = Vec::with_capacity(self.len());
+        vec.extend(std::iter::from_fn(|| self.pop_front_mut()));
+        vec
+    }
+
+    /// Moves the elements into a `Vec` and sorts it.
+    pub fn into_sorted_vec(self) -> Vec<T>
+    where
+        T: Ord,
+    {
+        let mut vec = self.into_vec();
+        vec.sort();
+        vec
+    }
+
+    /// Splits the list into runs of consecutive elements with equal keys,
+    /// in order.
+    pub fn group_by<K, F>(mut self, mut key_fn: F) -> LinkedList<LinkedList<T>>
+    where
+        K: PartialEq,
+        F: FnMut(&T) -> K,
+    {
+        let groups = LinkedList::new();
+        let mut current: Option<(K, LinkedList<T>)> = None;
+        while let Some(node) = self.unlink_front_node() {
+            let key = key_fn(unsafe { &(&*node).inner });
+            let group = match &mut current {
+                Some((k, group)) if *k == key => group,
+                _ => {
+                    if let Some((_, full)) = current.take() {
+                        groups.push_back(full);
+                    }
+                    &mut current.insert((key, LinkedList::new())).1
+                }
+            };
+            group.link_back_node(node);
+            group.grow_len();
+        }
+        if let Some((_, last)) = current {
+            groups.push_back(last);
+        }
+        groups
+    }
+
+    /// Distributes the elements over `shards` lists, putting each one in the
+    /// list `selector` picks for it, modulo `shards`. Every shard keeps the
+    /// elements in their original order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shards` is 0.
+    pub fn shard_by<F>(mut self, shards: usize, mut selector: F) -> Vec<LinkedList<T>>
+    where
+        F: FnMut(&T) -> usize,
+    {
+        assert!(shards != 0, "shard count must be non-zero");
+        let mut lists: Vec<_> = (0..shards).map(|_| LinkedList::new()).collect();
+        while let Some(node) = self.unlink_front_node() {
+            let shard = &mut lists[selector(unsafe { &(&*node).inner }) % shards];
+            shard.link_back_node(node);
+            shard.grow_len();
+        }
+        lists
+    }
+
+    /// Drops elements from the front for as long as `f` holds, returning how
+    /// many it dropped. Each one is dropped as soon as `f` accepts it, and
+    /// its node freed right away.
+    pub fn trim_front_while<F>(&mut self, mut f: F) -> usize
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut dropped = 0;
+        while let Some((head, _)) = self.node_ends() {
+            let node = unsafe { &*head };
+            let live = !node.removed.load(Ordering::Relaxed);
+            if live && !f(&node.inner) {
+                break;
+            }
+            self.unlink_node(head);
+            if live {
+                dropped += 1;
+                *self.len.get_mut() -= 1;
+            }
+            unsafe { free_node(head) };
+        }
+        dropped
+    }
+
+    /// Keeps only the elements `f` returns `true` for, in order.
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.retain_counted(f);
+    }
+
+    /// Removes every element equal to one before it, anywhere in the list,
+    /// keeping the first of each.
+    pub fn dedup_all(&mut self)
+    where
+        T: Eq + Hash,
+    {
+        let mut seen = HashSet::new();
+        // only elements that are kept go into `seen`, so none of them is
+        // dropped before it is
+        self.retain(|t| seen.insert(unsafe { &*(t as *const T) }));
+    }
+
+    /// Like [`retain`](Self::retain), returning how many elements it removed.
+    pub fn retain_counted<F>(&mut self, f: F) -> usize
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.retain_from(f, true)
+    }
+
+    /// Like [`retain`](Self::retain), but `f` also gets each element's
+    /// original index.
+    pub fn retain_indexed<F>(&mut self, mut f: F)
+    where
+        F: FnMut(usize, &T) -> bool,
+    {
+        let mut index = 0;
+        self.retain(|t| {
+            let keep = f(index, t);
+            index += 1;
+            keep
+        });
+    }
+
+    /// Like [`retain`](Self::retain), but visits the elements back to front.
+    ///
+    /// A stateless `f` keeps the same elements either way; this matters for
+    /// ones that count or remember what they have seen, such as keeping only
+    /// the last few elements.
+    pub fn retain_rev<F>(&mut self, f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.retain_from(f, false);
+    }
+
+    fn retain_from<F>(&mut self, mut f: F, forward: bool) -> usize
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let Some((head, tail)) = self.node_ends() else {
+            return 0;
+        };
+        let (first, last) = if forward { (head, tail) } else { (tail, head) };
+
+        let mut removed = 0;
+        let mut current = first;
+        while !current.is_null() {
+            let node = unsafe { &*current };
+            let next = if current == last {
+                ptr::null_mut()
+            } else if forward {
+                node.next.load(Ordering::Relaxed)
+            } else {
+                node.prev.load(Ordering::Relaxed)
+            };
+
+            let live = !node.removed.load(Ordering::Relaxed);
+            if !live || !f(&node.inner) {
+                self.unlink_node(current);
+                if live {
+                    removed += 1;
+                    *self.len.get_mut() -= 1;
+                }
+                unsafe { free_node(current) };
+            }
+            current = next;
+        }
+        removed
+    }
+
+    /// Reverses the order of the elements in `start..end`, leaving the rest in
+    /// place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start > end` or if `end` is greater than the length.
+    pub fn reverse_range(&mut self, start: usize, end: usize) {
+        let len = *self.len.get_mut();
+        assert!(
+            start <= end,
+            "range start {start} is greater than range end {end}"
+        );
+        assert!(
+            end <= len,
+            "range end {end} is out of range for a list of length {len}"
+        );
+        if end - start < 2 {
+            return;
+        }
+
+        // without removed nodes positions map directly onto links
+        self.retain(|_| true);
+        let (head, tail) = self.node_ends().expect("list is not empty");
+        let nth = |n: usize| {
+            let mut node = head;
+            for _ in 0..n {
+                node = unsafe { &*node }.next.load(Ordering::Relaxed);
+            }
+            node
+        };
+        let (first, last) = (nth(start), nth(end - 1));
+        let before = unsafe { &*first }.prev.load(Ordering::Relaxed);
+        let after = unsafe { &*last }.next.load(Ordering::Relaxed);
+
+        let mut current = first;
+        loop {
+            let node = unsafe { &*current };
+            let next = node.next.load(Ordering::Relaxed);
+            node.next
+                .store(node.prev.load(Ordering::Relaxed), Ordering::Relaxed);
+            node.prev.store(next, Ordering::Relaxed);
+            if current == last {
+                break;
+            }
+            current = next;
+        }
+
+        let new_head = if first == head {
+            unsafe { &*last }
+                .prev
+                .store(ptr::null_mut(), Ordering::Relaxed);
+            last
+        } else {
+            unsafe { &*last }.prev.store(before, Ordering::Relaxed);
+            unsafe { &*before }.next.store(last, Ordering::Relaxed);
+            head
+        };
+        let new_tail = if last == tail {
+            unsafe { &*first }
+                .next
+                .store(ptr::null_mut(), Ordering::Relaxed);
+            first
+        } else {
+            unsafe { &*first }.next.store(after, Ordering::Relaxed);
+            unsafe { &*after }.prev.store(first, Ordering::Relaxed);
+            tail
+        };
+        self.set_ends(new_head, new_tail);
+    }
+
+    /// Splits the list right after the first element equal to `value`,
+    /// returning everything behind it, or `None` if there is no such element.
+    pub fn split_after(&mut self, value: &T) -> Option<LinkedList<T>>
+    where
+        T: PartialEq,
+    {
+        let anchor = Anchor::get_mut(&mut self.anchor)?;
+        let mut current = anchor.head;
+        let mut at = 0;
+
+        while !current.is_null() {
+            let node = unsafe { &*current };
+            if !node.removed.load(Ordering::Relaxed) {
+                at += 1;
+                if *node.inner == *value {
+                    return Some(self.split_after_node(current, at));
+                }
+            }
+            current = anchor.next_of(current);
+        }
+        None
+    }
+
+    /// Splits the list between the first two adjacent elements `f` returns
+    /// `true` for, returning everything from the second one on, or `None` if
+    /// there is no such pair.
+    pub fn split_when<F>(&mut self, mut f: F) -> Option<LinkedList<T>>
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        let anchor = Anchor::get_mut(&mut self.anchor)?;
+        let mut current = anchor.head;
+        let mut prev: Option<NodePtr<T>> = None;
+        let mut at = 0;
+
+        while !current.is_null() {
+            let node = unsafe { &*current };
+            if !node.removed.load(Ordering::Relaxed) {
+                if let Some(prev) = prev {
+                    if f(&unsafe { &*prev }.inner, &node.inner) {
+                        return Some(self.split_after_node(prev, at));
+                    }
+                }
+                prev = Some(current);
+                at += 1;
+            }
+            current = anchor.next_of(current);
+        }
+        None
+    }
+
+    /// Detaches the first `n` elements into a new list, or all of them if
+    /// there are fewer.
+    pub fn take_front(&mut self, n: usize) -> LinkedList<T> {
+        let mut front = LinkedList::new();
+        if n == 0 {
+            return front;
+        }
+        if n >= *self.len.get_mut() {
+            self.swap(&mut front);
+            return front;
+        }
+
+        let anchor = Anchor::get_mut(&mut self.anchor).expect("list is not empty");
+        let mut current = anchor.head;
+        let mut at = 0;
+        loop {
+            if !unsafe { &*current }.removed.load(Ordering::Relaxed) {
+                at += 1;
+                if at == n {
+                    break;
+                }
+            }
+            current = anchor.next_of(current);
+        }
+
+        front = self.split_after_node(current, n);
+        self.swap(&mut front);
+        front
+    }
+
+    /// Detaches every node after `node`, the `at`-th live element, into a new
+    /// list.
+    fn split_after_node(&mut self, node: *mut Linked<Node<T>>, at: usize) -> LinkedList<T> {
+        let mut other = LinkedList::new();
+        let len = self.len.get_mut();
+        let anchor = Anchor::get_mut(&mut self.anchor).expect("node belongs to the list");
+
+        if node == anchor.tail {
+            return other;
+        }
+
+        let head = unsafe { &*node }.next.load(Ordering::Relaxed);
+        unsafe { &*head }
+            .prev
+            .store(ptr::null_mut(), Ordering::Relaxed);
+
+        other.set_ends(head, anchor.tail);
+        *other.len.get_mut() = *len - at;
+        *other.max_len.get_mut() = *len - at;
+
+        anchor.tail = node;
+        *len = at;
+        *self.version.get_mut() += 1;
+
+        other
+    }
+
+    /// The head and tail of an exclusively owned list.
+    fn node_ends(&mut self) -> Option<(NodePtr<T>, NodePtr<T>)> {
+        Anchor::get_mut(&mut self.anchor).map(|anchor| (anchor.head, anchor.tail))
+    }
+
+    /// The first live node of an exclusively owned list matching `f`.
+    fn find_node_mut<F>(&mut self, mut f: F) -> Option<NodePtr<T>>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let (head, tail) = self.node_ends()?;
+        let mut current = head;
+        loop {
+            let node = unsafe { &*current };
+            if !node.removed.load(Ordering::Relaxed) && f(&node.inner) {
+                return Some(current);
+            }
+            if current == tail {
+                return None;
+            }
+            current = node.next.load(Ordering::Relaxed);
+        }
+    }
+
+    /// Takes `node` out of an exclusively owned list, leaving it allocated
+    /// and `len` as it is.
+    fn unlink_node(&mut self, node: NodePtr<T>) {
+        let (head, tail) = self.node_ends().expect("node belongs to the list");
+        let prev = unsafe { &*node }.prev.load(Ordering::Relaxed);
+        let next = unsafe { &*node }.next.load(Ordering::Relaxed);
+
+        let new_head = if node != head {
+            unsafe { &*prev }.next.store(next, Ordering::Relaxed);
+            head
+        } else if node != tail {
+            next
+        } else {
+            ptr::null_mut()
+        };
+        let new_tail = if node != tail {
+            unsafe { &*next }.prev.store(prev, Ordering::Relaxed);
+            tail
+        } else if node != head {
+            prev
+        } else {
+            ptr::null_mut()
+        };
+        self.set_ends(new_head, new_tail);
+    }
+
+    /// Links a detached `node` in front of an exclusively owned list.
+    fn link_front_node(&mut self, node: NodePtr<T>) {
+        let ends = self.node_ends();
+        let n = unsafe { &*node };
+        n.prev.store(ptr::null_mut(), Ordering::Relaxed);
+        match ends {
+            Some((head, tail)) => {
+                n.next.store(head, Ordering::Relaxed);
+                unsafe { &*head }.prev.store(node, Ordering::Relaxed);
+                self.set_ends(node, tail);
+            }
+            None => self.set_ends(node, node),
+        }
+    }
+
+    /// Links a detached `node` behind an exclusively owned list.
+    fn link_back_node(&mut self, node: NodePtr<T>) {
+        let ends = self.node_ends();
+        let n = unsafe { &*node };
+        n.next.store(ptr::null_mut(), Ordering::Relaxed);
+        match ends {
+            Some((head, tail)) => {
+                n.prev.store(tail, Ordering::Relaxed);
+                unsafe { &*tail }.next.store(node, Ordering::Relaxed);
+                self.set_ends(head, node);
+            }
+            None => self.set_ends(node, node),
+        }
+    }
+
+    /// Unlinks the first live node of an exclusively owned list, freeing any
+    /// removed ones in front of it.
+    fn unlink_front_node(&mut self) -> Option<NodePtr<T>> {
+        loop {
+            let (head, _) = self.node_ends()?;
+            self.unlink_node(head);
+            if !unsafe { &*head }.removed.load(Ordering::Relaxed) {
+                *self.len.get_mut() -= 1;
+                return Some(head);
+            }
+            unsafe { free_node(head) };
+        }
+    }
+
+    /// Pops the front of an exclusively owned list, without going through the
+    /// collector.
+    fn pop_front_mut(&mut self) -> Option<T> {
+        let node = self.unlink_front_node()?;
+        let node = Linked::into_inner(*unsafe { Box::from_raw(node) });
+        Some(ManuallyDrop::into_inner(node.inner))
+    }
+
+    /// Points an exclusively owned list at `head..=tail`, allocating or
+    /// freeing the anchor as the list stops or starts being empty.
+    fn set_ends(&mut self, head: *mut Linked<Node<T>>, tail: *mut Linked<Node<T>>) {
+        *self.version.get_mut() += 1;
+        let anchor = self.anchor.get_mut();
+        match (anchor.is_null(), head.is_null()) {
+            (true, true) => {}
+            (true, false) => {
+                *anchor = self.collector.link_boxed(Anchor {
+                    head,
+                    tail,
+                    status: Status::Stable,
+                });
+            }
+            (false, true) => {
+                let _ = unsafe { Box::from_raw(*anchor) };
+                *anchor = ptr::null_mut();
+            }
+            (false, false) => {
+                let anchor = unsafe { &mut **anchor };
+                anchor.head = head;
+                anchor.tail = tail;
+            }
+        }
+    }
+
+    /// Hands an unlinked node to the collector.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been removed from the list by the calling thread.
+    #[inline]
+    unsafe fn retire_node(&self, ptr: NodePtr<T>) {
+        #[cfg(debug_assertions)]
+        assert!(!(&*ptr).retired.swap(true, RELAXED), "node retired twice");
+        self.collector.retire(ptr, reclaim::boxed::<Node<T>>);
+    }
+
+    /// Takes the element out of a node that has just been unlinked, and
+    /// retires the node.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been removed from the list by the calling thread, which
+    /// must also have claimed it.
+    #[inline]
+    unsafe fn consume_and_retire(&self, ptr: *mut Linked<Node<T>>) -> T {
+        debug_assert!(
+            (&*ptr).removed.load(RELAXED),
+            "took the element of a node nobody claimed"
+        );
+        let data = ptr::read(&(&*ptr).inner);
+        self.retire_node(ptr);
+        self.shrink_len();
+        ManuallyDrop::into_inner(data)
+    }
+}
+
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> LinkedList<LinkedList<T>> {
+    /// Concatenates the inner lists, front to back, into one list.
+    pub fn flatten(mut self) -> LinkedList<T> {
+        let mut flat = LinkedList::new();
+        while let Some(mut list) = self.pop_front_mut() {
+            flat.append(&mut list);
+        }
+        flat
+    }
+}
+
+impl<A, B> LinkedList<(A, B)> {
+    /// Splits the pairs into a list of first and a list of second halves,
+    /// the inverse of [`zip`](LinkedList::zip).
+    pub fn unzip(mut self) -> (LinkedList<A>, LinkedList<B>) {
+        let (a, b) = (LinkedList::new(), LinkedList::new());
+        while let Some((x, y)) = self.pop_front_mut() {
+            a.push_back(x);
+            b.push_back(y);
+        }
+        (a, b)
+    }
+}
+
+impl<T> Drop for LinkedList<T> {
+    fn drop(&mut self) {
+        let anchor = *self.anchor.get_mut();
+        if anchor.is_null() {
+            return;
+        }
+
+        let anchor = unsafe { Box::from_raw(anchor) };
+        drop(Chain {
+            head: anchor.head,
+            tail: anchor.tail,
+        });
+    }
+}
+
+/// The nodes `head..=tail` of a list, owned by nobody but this.
+struct Chain<T> {
+    head: *mut Linked<Node<T>>,
+    tail: *mut Linked<Node<T>>,
+}
+
+impl<T> Chain<T> {
+    fn pop_front(&mut self) -> Option<Box<Linked<Node<T>>>> {
+        if self.head.is_null() {
+            return None;
+        }
+        let node = unsafe { Box::from_raw(self.head) };
+        #[cfg(debug_assertions)]
+        assert!(
+            !node.retired.load(Ordering::Relaxed),
+            "freeing a node that was retired"
+        );
+        self.head = if self.head == self.tail {
+            ptr::null_mut()
+        } else {
+            node.next.load(Ordering::Relaxed)
+        };
+        Some(node)
+    }
+
+    fn drop_payload(node: &mut Node<T>) {
+        if !*node.removed.get_mut() {
+            unsafe { ManuallyDrop::drop(&mut node.inner) };
+        }
+    }
+}
+
+impl<T> Drop for Chain<T> {
+    fn drop(&mut self) {
+        // Frees the rest of the nodes if an element's destructor panics.
+        struct DropGuard<'a, T>(&'a mut Chain<T>);
+
+        impl<T> Drop for DropGuard<'_, T> {
+            fn drop(&mut self) {
+                while let Some(mut node) = self.0.pop_front() {
+                    Chain::drop_payload(&mut node);
+                }
+            }
+        }
+
+        while let Some(mut node) = self.pop_front() {
+            let guard = DropGuard(self);
+            Chain::drop_payload(&mut node);
+            mem::forget(guard);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::VecDeque,
+        panic::{self, AssertUnwindSafe},
+        sync::{atomic::AtomicUsize, Arc, Barrier},
+        thread,
+        time::Duration,
+    };
+
+    use super::*;
+
+    fn ends(list: &LinkedList<i32>) -> (i32, i32) {
+        let anchor = unsafe { &*list.anchor.load(Ordering::Acquire) };
+        unsafe { (*(&*anchor.head).inner, *(&*anchor.tail).inner) }
+    }
+
+    #[test]
+    fn push_back_pop_front() {
+        let list = LinkedList::new();
+        list.push_back(1);
+        assert_eq!(ends(&list), (1, 1));
+        list.push_back(2);
+        assert_eq!(ends(&list), (1, 2));
+        list.push_back(3);
+        assert_eq!(ends(&list), (1, 3));
+
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.pop_front().unwrap(), 1);
+        assert_eq!(list.pop_front().unwrap(), 2);
+        assert_eq!(list.pop_front().unwrap(), 3);
+        assert!(list.pop_front().is_none());
+        assert_eq!(list.len(), 0);
+    }
+
+    #[test]
+    fn push_front_pop_front() {
+        let list = LinkedList::new();
+
+        list.push_front(1);
+        assert_eq!(ends(&list).0, 1);
+
+        list.push_front(2);
+        assert_eq!(ends(&list).0, 2);
+
+        list.push_front(3);
+        assert_eq!(ends(&list).0, 3);
+
+        let anchor = unsafe { &*list.anchor.load(Ordering::Acquire) };
+        let head = anchor.head;
+        let head_next = unsafe { (&*head).next.load(Ordering::Acquire) };
+        let head_next_2 = unsafe { (&*head_next).next.load(Ordering::Acquire) };
+
+        assert_eq!(head_next_2, anchor.tail);
+        assert_eq!(unsafe { (&*head_next).prev.load(Ordering::Acquire) }, head);
+        assert_eq!(
+            unsafe { (&*head_next_2).prev.load(Ordering::Acquire) },
+            head_next
+        );
+
+        assert_eq!(list.len(), 3);
+
+        assert_eq!(list.pop_front().unwrap(), 3);
+        let anchor = unsafe { &*list.anchor.load(Ordering::Acquire) };
+        assert_eq!(anchor.head, head_next);
+        assert_eq!(list.pop_front().unwrap(), 2);
+        assert_eq!(list.pop_front().unwrap(), 1);
+        assert!(list.pop_front().is_none());
+        assert_eq!(list.len(), 0);
+    }
+
+    #[test]
+    fn push_back_pop_back() {
+        let list = LinkedList::new();
+        list.push_back(1);
+        list.push_back(2);
+        list.push_back(3);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.pop_back().unwrap(), 3);
+        assert_eq!(list.pop_back().unwrap(), 2);
+        assert_eq!(list.pop_back().unwrap(), 1);
+        assert!(list.pop_back().is_none());
+        assert_eq!(list.len(), 0);
+    }
+
+    const ITER: u32 = 100;
+
+    #[test]
+    fn push_pop_multi() {
+        let list = LinkedList::new();
+        let b = Barrier::new(2);
+        thread::scope(|s| {
+            s.spawn(|| {
+                b.wait();
+                for i in 0..ITER {
+                    list.push_front(i);
+                }
+            });
+
+            s.spawn(|| {
+                b.wait();
+                thread::sleep(Duration::from_millis(10));
+                for _ in 0..ITER {
+                    let _ = list.pop_front();
+                }
+            });
+        });
+        assert_eq!(list.len(), 0);
+    }
+
+    #[test]
+    fn push_back_pop_front_temp() {
+        let list = LinkedList::new();
+        list.push_back(1);
+        list.push_back(2);
+        assert_eq!(list.pop_front().unwrap(), 1);
+        assert_eq!(list.pop_front().unwrap(), 2);
+    }
+
+    #[test]
+    fn push_front_pop_back_temp() {
+        let list = LinkedList::new();
+        list.push_front(1);
+        list.push_front(2);
+        assert_eq!(list.pop_back().unwrap(), 1);
+        assert_eq!(list.pop_back().unwrap(), 2);
+    }
+
+    #[test]
+    fn compact_relinks_in_order() {
+        let mut list = LinkedList::new();
+        let mut expected = VecDeque::new();
+        for i in 0..100 {
+            list.push_back(i);
+            expected.push_back(i);
+            list.push_front(-i);
+            expected.push_front(-i);
+            if i % 3 == 0 {
+                assert_eq!(list.pop_back(), expected.pop_back());
+                assert_eq!(list.pop_front(), expected.pop_front());
+            }
+        }
+
+        list.compact();
+        assert_eq!(list.len(), expected.len());
+
+        let anchor = unsafe { &*list.anchor.load(Ordering::Acquire) };
+        let mut current = anchor.tail;
+        for x in expected.iter().rev() {
+            assert_eq!(unsafe { *(&*current).inner }, *x);
+            current = unsafe { (&*current).prev.load(Ordering::Acquire) };
+        }
+
+        for x in expected {
+            assert_eq!(list.pop_front(), Some(x));
+        }
+        assert!(list.pop_front().is_none());
+    }
+
+    #[test]
+    fn try_fold_stops_at_err() {
+        let mut list = LinkedList::new();
+        for i in 1..=5 {
+            list.push_back(i);
+        }
+
+        let sum = list.try_fold(0, |acc, x| Ok::<_, i32>(acc + x));
+        assert_eq!(sum, Ok(15));
+
+        let mut visited = 0;
+        let sum = list.try_fold(0, |acc, &x| {
+            visited += 1;
+            if x > 3 {
+                Err(x)
+            } else {
+                Ok(acc + x)
+            }
+        });
+        assert_eq!(sum, Err(4));
+        assert_eq!(visited, 4);
+    }
+
+    #[test]
+    fn scan_running_sum() {
+        let mut list = LinkedList::new();
+        for i in 1..=3 {
+            list.push_back(i);
+        }
+        let mut sums = list.scan(0, |sum, x| {
+            *sum += x;
+            *sum
+        });
+        assert_eq!(sums.iter_owned().collect::<Vec<_>>(), [1, 3, 6]);
+    }
+
+    #[test]
+    fn split_after_value() {
+        let mut list = LinkedList::new();
+        for i in 1..=4 {
+            list.push_back(i);
+        }
+
+        assert!(list.split_after(&7).is_none());
+
+        let mut tail = list.split_after(&2).unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(tail.len(), 2);
+
+        {
+            assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 2]);
+            assert_eq!(tail.iter().copied().collect::<Vec<_>>(), [3, 4]);
+        }
+
+        assert_eq!(list.pop_back(), Some(2));
+        assert_eq!(tail.pop_front(), Some(3));
+        assert_eq!(tail.pop_back(), Some(4));
+
+        let empty = list.split_after(&1).unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(list.pop_front(), Some(1));
+    }
+
+    #[test]
+    fn split_when_gap() {
+        let mut list = LinkedList::new();
+        for i in [1, 2, 5, 6] {
+            list.push_back(i);
+        }
+        assert!(list.split_when(|a, b| b - a > 5).is_none());
+
+        let mut tail = list.split_when(|a, b| b - a > 2).unwrap();
+        assert_eq!(list.iter_owned().collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(tail.iter_owned().collect::<Vec<_>>(), [5, 6]);
+        assert_eq!((list.len(), tail.len()), (2, 2));
+    }
+
+    #[test]
+    fn observe_counts_operations() {
+        use std::sync::Arc;
+
+        let mut list = LinkedList::new();
+        let pushes = Arc::new(AtomicUsize::new(0));
+        let pops = Arc::new(AtomicUsize::new(0));
+        let removals = Arc::new(AtomicUsize::new(0));
+        {
+            let pushes = pushes.clone();
+            let pops = pops.clone();
+            let removals = removals.clone();
+            list.observe(move |op| match op {
+                Operation::PushFront | Operation::PushBack => {
+                    pushes.fetch_add(1, Ordering::Relaxed);
+                }
+                Operation::PopFront | Operation::PopBack => {
+                    pops.fetch_add(1, Ordering::Relaxed);
+                }
+                Operation::Remove => {
+                    removals.fetch_add(1, Ordering::Relaxed);
+                }
+            });
+        }
+
+        list.push_back(1);
+        list.push_front(2);
+        list.push_back(3);
+        list.pop_front();
+        list.pop_back();
+        list.pop_back();
+        // popping an empty list is not an operation
+        list.pop_front();
+
+        assert_eq!(pushes.load(Ordering::Relaxed), 3);
+        assert_eq!(pops.load(Ordering::Relaxed), 3);
+
+        let guard = list.guard();
+        let handle = list.push_back_return_ref(4, &guard);
+        assert_eq!(list.remove(handle), Some(4));
+        let stale = list.push_back_return_ref(5, &guard);
+        assert_eq!(list.pop_back(), Some(5));
+        // already popped, so nothing to report
+        assert_eq!(list.remove(stale), None);
+        drop(guard);
+        list.push_back(6);
+        assert_eq!(list.find(|&t| t == 6).unwrap().remove(), Some(6));
+
+        assert_eq!(pushes.load(Ordering::Relaxed), 6);
+        assert_eq!(pops.load(Ordering::Relaxed), 4);
+        assert_eq!(removals.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn pop_front_blocking_waits_for_push() {
+        let list = BlockingList::new();
+        thread::scope(|s| {
+            let consumer = s.spawn(|| list.pop_front_blocking(10));
+            thread::sleep(Duration::from_millis(50));
+            list.push_back(7);
+            assert_eq!(consumer.join().unwrap(), 7);
+        });
+        list.push_back(8);
+        assert_eq!(list.pop_front_blocking(0), 8);
+        assert!(list.waiters.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn with_defaults_zeroes() {
+        let mut list = LinkedList::<i32>::with_defaults(5);
+        assert_eq!(list.len(), 5);
+        assert_eq!(list.max_len_observed(), 5);
+        assert_eq!(list.iter_owned().collect::<Vec<_>>(), [0; 5]);
+    }
+
+    #[test]
+    fn version_changes_on_push_and_pop() {
+        let mut list = LinkedList::new();
+        let v0 = list.version();
+        list.push_back(1);
+        list.push_front(0);
+        let v1 = list.version();
+        assert!(v1 > v0);
+        assert_eq!(list.to_vec(), [0, 1]);
+        assert_eq!(list.count(&list.guard()), 2);
+        assert_eq!(list.version(), v1);
+        list.pop_back();
+        assert!(list.version() > v1);
+    }
+
+    #[test]
+    fn version_validates_concurrent_reads() {
+        let list = LinkedList::new();
+        let done = AtomicBool::new(false);
+        thread::scope(|s| {
+            for _ in 0..2 {
+                s.spawn(|| {
+                    for i in 0..ITER * 10 {
+                        list.push_back(i);
+                        list.push_front(i);
+                        list.pop_back();
+                    }
+                    done.store(true, Ordering::Relaxed);
+                });
+            }
+            s.spawn(|| {
+                while !done.load(Ordering::Relaxed) {
+                    let guard = list.guard();
+                    let before = list.version();
+                    let (a, b) = (list.count(&guard), list.count(&guard));
+                    if list.version() == before {
+                        assert_eq!(a, b);
+                    }
+                }
+            });
+        });
+    }
+
+    #[test]
+    fn version_only_grows() {
+        let list = LinkedList::new();
+        let done = AtomicBool::new(false);
+        let v0 = list.version();
+        thread::scope(|s| {
+            for _ in 0..2 {
+                s.spawn(|| {
+                    for i in 0..ITER {
+                        list.push_back(i);
+                        list.pop_front();
+                    }
+                    done.store(true, Ordering::Relaxed);
+                });
+            }
+            s.spawn(|| {
+                let mut last = list.version();
+                while !done.load(Ordering::Relaxed) {
+                    let v = list.version();
+                    assert!(v >= last);
+                    last = v;
+                }
+            });
+        });
+        // retries may count twice, but every push and every pop that got an
+        // element counts at least once
+        let pops = 2 * ITER as usize - list.len();
+        assert!(list.version() - v0 >= (2 * ITER as usize + pops) as u64);
+    }
+
+    #[test]
+    fn mut_methods_change_version() {
+        let mut list = LinkedList::new();
+        for i in 0..4 {
+            list.push_back(i);
+        }
+        let v0 = list.version();
+        list.retain(|&x| x != 2);
+        let v1 = list.version();
+        assert!(v1 > v0);
+        let mut other = LinkedList::new();
+        other.push_back(7);
+        list.append(&mut other);
+        let v2 = list.version();
+        assert!(v2 > v1);
+        list.clear();
+        assert!(list.version() > v2);
+    }
+
+    #[test]
+    fn pop_back_chunks() {
+        let list = LinkedList::new();
+        for i in 0..10 {
+            list.push_back(i);
+        }
+
+        assert_eq!(list.pop_back_chunk(4), [9, 8, 7, 6]);
+        assert_eq!(list.pop_back_chunk(4), [5, 4, 3, 2]);
+        assert_eq!(list.pop_back_chunk(4), [1, 0]);
+        assert!(list.pop_back_chunk(4).is_empty());
+        assert_eq!(list.len(), 0);
+    }
+
+    #[test]
+    fn push_pop_both_ends_multi() {
+        use std::sync::Mutex;
+
+        const THREADS: u32 = 4;
+        let list = LinkedList::new();
+        let popped = Mutex::new(Vec::new());
+        let b = Barrier::new(THREADS as usize);
+
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                let (list, popped, b) = (&list, &popped, &b);
+                s.spawn(move || {
+                    b.wait();
+                    let mut local = Vec::new();
+                    for i in 0..ITER {
+                        let value = t * ITER + i;
+                        if i % 2 == 0 {
+                            list.push_back(value);
+                        } else {
+                            list.push_front(value);
+                        }
+                        let popped = if (i + t) % 3 == 0 {
+                            list.pop_back()
+                        } else {
+                            list.pop_front()
+                        };
+                        local.extend(popped);
+                    }
+                    popped.lock().unwrap().extend(local);
+                });
+            }
+        });
+
+        let mut popped = popped.into_inner().unwrap();
+        while let Some(value) = list.pop_front() {
+            popped.push(value);
+        }
+        popped.sort();
+        assert_eq!(popped, (0..THREADS * ITER).collect::<Vec<_>>());
+        assert_eq!(list.len(), 0);
+    }
+
+    #[test]
+    fn steal_exactly_once() {
+        use std::sync::Mutex;
+
+        const THIEVES: usize = 3;
+        let mut list = LinkedList::new();
+        let taken = Mutex::new(Vec::new());
+        let b = Barrier::new(THIEVES + 1);
+
+        thread::scope(|s| {
+            s.spawn(|| {
+                b.wait();
+                let mut local = Vec::new();
+                for i in 0..ITER * 4 {
+                    list.push_back(i);
+                    if i % 2 == 1 {
+                        local.extend(list.pop_back());
+                    }
+                }
+                while let Some(i) = list.pop_back() {
+                    local.push(i);
+                }
+                taken.lock().unwrap().extend(local);
+            });
+
+            for _ in 0..THIEVES {
+                s.spawn(|| {
+                    b.wait();
+                    let mut local = Vec::new();
+                    for _ in 0..ITER {
+                        local.extend(list.steal());
+                    }
+                    taken.lock().unwrap().extend(local);
+                });
+            }
+        });
+
+        let mut taken = taken.into_inner().unwrap();
+        taken.extend(list.drain_while(|_| true));
+        taken.sort();
+        assert_eq!(taken, (0..ITER * 4).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn index_from_front() {
+        let mut list = LinkedList::new();
+        for i in 1..=4 {
+            list.push_back(i);
+        }
+        assert_eq!(*list.index(0), 1);
+        assert_eq!(*list.index(2), 3);
+        assert_eq!(*list.index(3), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn index_out_of_bounds() {
+        let mut list = LinkedList::new();
+        list.push_back(1);
+        list.index(1);
+    }
+
+    #[test]
+    fn rget_from_back() {
+        let mut list = LinkedList::new();
+        for i in 1..=3 {
+            list.push_back(i);
+        }
+        assert_eq!(list.rget(0), Some(&3));
+        assert_eq!(list.rget(2), Some(&1));
+        assert_eq!(list.rget(3), None);
+    }
+
+    #[test]
+    fn both_ends_at_once() {
+        let mut list = LinkedList::new();
+        {
+            assert_eq!(list.ends(), (None, None));
+        }
+        list.push_back(2);
+        {
+            let (front, back) = list.ends();
+            assert!(ptr::eq(front.unwrap(), back.unwrap()));
+        }
+        list.push_front(1);
+        list.push_back(3);
+        assert_eq!(list.ends(), (Some(&1), Some(&3)));
+    }
+
+    #[test]
+    fn to_vec_keeps_list() {
+        let mut list = LinkedList::new();
+        for i in 1..=3 {
+            list.push_back(i);
+        }
+        {
+            assert_eq!(list.to_vec(), [1, 2, 3]);
+        }
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_front(), Some(2));
+        assert_eq!(list.pop_front(), Some(3));
+    }
+
+    #[test]
+    fn drain_while_prefix() {
+        let mut list = LinkedList::new();
+        for i in 1..=4 {
+            list.push_back(i);
+        }
+        assert_eq!(list.drain_while(|&x| x < 3), [1, 2]);
+        assert_eq!(list.len(), 2);
+        assert_eq!(list.pop_front(), Some(3));
+        assert_eq!(list.pop_front(), Some(4));
+        assert!(list.drain_while(|_| true).is_empty());
+    }
+
+    #[test]
+    fn empty_list_has_no_anchor() {
+        let no_anchor = |list: &LinkedList<i32>| list.anchor.load(Ordering::Acquire).is_null();
+
+        let mut list = LinkedList::new();
+        assert!(no_anchor(&list));
+
+        list.push_back(1);
+        list.push_front(0);
+        assert!(!no_anchor(&list));
+        list.pop_back();
+        list.pop_back();
+        assert!(no_anchor(&list));
+
+        list.push_front(1);
+        list.push_back(2);
+        let rest = list.split_after(&2).unwrap();
+        assert!(no_anchor(&rest));
+        list.compact();
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_front(), Some(2));
+        assert!(no_anchor(&list));
+
+        list.compact();
+        assert!(list.split_after(&1).is_none());
+        assert!(no_anchor(&list));
+    }
+
+    #[test]
+    fn remove_through_handle() {
+        let mut list = LinkedList::new();
+        {
+            let guard = list.guard();
+            list.push_back(1);
+            let handle = list.push_back_return_ref(2, &guard);
+            list.push_back(3);
+            list.push_back(4);
+
+            assert_eq!(list.remove(handle), Some(2));
+            assert_eq!(list.len(), 3);
+
+            let handle = list.push_back_return_ref(5, &guard);
+            assert_eq!(list.pop_back(), Some(5));
+            assert_eq!(list.remove(handle), None);
+        }
+        assert_eq!(list.to_vec(), vec![1, 3, 4]);
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_front(), Some(3));
+        assert_eq!(list.pop_front(), Some(4));
+        assert_eq!(list.pop_front(), None);
+    }
+
+    #[test]
+    #[cfg_attr(
+        feature = "striped-len",
+        ignore = "pushes don't track the high-water mark with striped-len"
+    )]
+    fn max_len_high_water_mark() {
+        let list = LinkedList::new();
+        for i in 0..100 {
+            list.push_back(i);
+        }
+        for _ in 0..50 {
+            list.pop_front();
+        }
+        assert_eq!(list.max_len_observed(), 100);
+        assert_eq!(list.len(), 50);
+
+        list.push_front(0);
+        assert_eq!(list.max_len_observed(), 100);
+    }
+
+    #[test]
+    fn iter_mut_doubles() {
+        let mut list = LinkedList::new();
+        list.push_back(1);
+        list.push_back(2);
+        list.push_back(3);
+
+        for x in list.iter_mut() {
+            *x *= 2;
+        }
+
+        assert_eq!(list.to_vec(), vec![2, 4, 6]);
+    }
+
+    #[test]
+    fn push_back_unique_by_key() {
+        let mut list = LinkedList::new();
+        assert!(list.push_back_unique((1, "a"), |&(k, _)| k));
+        assert!(list.push_back_unique((2, "b"), |&(k, _)| k));
+        assert!(!list.push_back_unique((1, "c"), |&(k, _)| k));
+        assert!(list.push_back_unique((3, "c"), |&(k, _)| k));
+
+        assert_eq!(list.to_vec(), vec![(1, "a"), (2, "b"), (3, "c")]);
+    }
+
+    #[test]
+    fn positions_of_matches() {
+        let mut list = LinkedList::new();
+        for i in [1, 2, 1, 2] {
+            list.push_back(i);
+        }
+        assert_eq!(list.positions(|&x| x == 2), [1, 3]);
+        assert!(list.positions(|&x| x == 3).is_empty());
+    }
+
+    #[test]
+    fn rposition_finds_last_match() {
+        let mut list = LinkedList::new();
+        assert_eq!(list.rposition(|&x| x == 2), None);
+
+        for i in [1, 2, 2, 3] {
+            list.push_back(i);
+        }
+        assert_eq!(list.rposition(|&x| x == 2), Some(2));
+        assert_eq!(list.rposition(|&x| x == 1), Some(0));
+        assert_eq!(list.rposition(|&x| x == 4), None);
+        assert_eq!(
+            list.iter().rev().copied().collect::<Vec<_>>(),
+            vec![3, 2, 2, 1]
+        );
+    }
+
+    struct PanicOnDrop {
+        value: i32,
+        drops: Arc<AtomicUsize>,
+    }
+
+    impl Drop for PanicOnDrop {
+        fn drop(&mut self) {
+            self.drops.fetch_add(1, Ordering::Relaxed);
+            if self.value == 2 {
+                panic!("dropping 2");
+            }
+        }
+    }
+
+    #[test]
+    fn panicking_destructor() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let push_all = |list: &LinkedList<PanicOnDrop>| {
+            for value in 1..=3 {
+                list.push_back(PanicOnDrop {
+                    value,
+                    drops: drops.clone(),
+                });
+            }
+        };
+
+        let mut list = LinkedList::new();
+        push_all(&list);
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| list.clear())).is_err());
+        assert_eq!(drops.load(Ordering::Relaxed), 3);
+        assert!(list.is_empty());
+
+        list.push_back(PanicOnDrop {
+            value: 4,
+            drops: drops.clone(),
+        });
+        assert_eq!(list.pop_front().map(|t| t.value), Some(4));
+        assert_eq!(drops.load(Ordering::Relaxed), 4);
+
+        push_all(&list);
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| drop(list))).is_err());
+        assert_eq!(drops.load(Ordering::Relaxed), 7);
+    }
+
+    #[test]
+    fn swap_contents() {
+        let mut a = LinkedList::new();
+        let mut b = LinkedList::new();
+        a.push_back(1);
+        a.push_back(2);
+        for i in 3..=5 {
+            b.push_back(i);
+        }
+
+        a.swap(&mut b);
+        assert_eq!((a.len(), b.len()), (3, 2));
+        assert_eq!(a.max_len_observed(), 3);
+        {
+            assert_eq!(a.to_vec(), vec![3, 4, 5]);
+            assert_eq!(b.to_vec(), vec![1, 2]);
+        }
+
+        assert_eq!(a.pop_front(), Some(3));
+        assert_eq!(b.pop_back(), Some(2));
+        a.swap(&mut LinkedList::new());
+        assert!(a.is_empty());
+    }
+
+    #[test]
+    fn chain_two_lists() {
+        let (mut a, mut b) = (LinkedList::new(), LinkedList::new());
+        a.push_back(1);
+        a.push_back(2);
+        b.push_back(3);
+        b.push_back(4);
+
+        let chained: Vec<_> = a.chain(&mut b).copied().collect();
+        assert_eq!(chained, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn take_front_n() {
+        let mut list = LinkedList::new();
+        for i in 1..=4 {
+            list.push_back(i);
+        }
+
+        let mut front = list.take_front(2);
+        assert_eq!((front.len(), list.len()), (2, 2));
+        assert_eq!(front.to_vec(), vec![1, 2]);
+        assert_eq!(list.to_vec(), vec![3, 4]);
+
+        assert!(list.take_front(0).is_empty());
+        let all = list.take_front(10);
+        assert_eq!((all.len(), list.len()), (2, 0));
+        assert_eq!(front.take_front(2).len(), 2);
+        assert!(front.is_empty());
+    }
+
+    #[test]
+    fn sum_and_product() {
+        let mut list = LinkedList::new();
+        assert_eq!(list.sum::<i32>(), 0);
+
+        for i in 1..=5 {
+            list.push_back(i);
+        }
+        assert_eq!(list.sum::<i32>(), 15);
+        assert_eq!(list.product::<i32>(), 120);
+    }
+
+    #[test]
+    fn get_or_insert_back_once() {
+        let mut list = LinkedList::new();
+        list.push_back(1);
+        let mut made = 0;
+        let mut make = || {
+            made += 1;
+            2
+        };
+
+        assert!(list.get_or_insert_back(|&x| x == 2, &mut make));
+        assert!(!list.get_or_insert_back(|&x| x == 2, &mut make));
+        assert_eq!(made, 1);
+
+        assert_eq!(list.to_vec(), vec![1, 2]);
+    }
+
+    #[test]
+    fn adjacent_pairs() {
+        let mut list = LinkedList::new();
+        assert_eq!(list.pairs().count(), 0);
+        list.push_back(1);
+        assert_eq!(list.pairs().count(), 0);
+
+        list.push_back(2);
+        list.push_back(3);
+        let pairs: Vec<_> = list.pairs().map(|(&a, &b)| (a, b)).collect();
+        assert_eq!(pairs, vec![(1, 2), (2, 3)]);
+    }
+
+    #[test]
+    fn clone_from_reuses_nodes() {
+        let mut source = LinkedList::new();
+        for i in 1..=3 {
+            source.push_back(i);
+        }
+
+        let mut longer = LinkedList::new();
+        for i in 10..15 {
+            longer.push_back(i);
+        }
+        longer.clone_from(&mut source);
+
+        let mut shorter = LinkedList::new();
+        shorter.push_back(10);
+        shorter.clone_from(&mut source);
+
+        let mut empty = LinkedList::new();
+        empty.clone_from(&mut source);
+
+        for list in [&mut longer, &mut shorter, &mut empty] {
+            assert_eq!(list.len(), 3);
+            assert_eq!(list.to_vec(), vec![1, 2, 3]);
+        }
+    }
+
+    #[test]
+    fn peek_mut_ends() {
+        let mut list = LinkedList::new();
+        assert_eq!(list.peek_front_mut(), None);
+        assert_eq!(list.peek_back_mut(), None);
+
+        for i in 1..=3 {
+            list.push_back(i);
+        }
+        *list.peek_front_mut().unwrap() = 7;
+        assert_eq!(list.peek_back_mut(), Some(&mut 3));
+        assert_eq!(
+            list.iter_mut().rev().map(|x| *x).collect::<Vec<_>>(),
+            vec![3, 2, 7]
+        );
+
+        assert_eq!(list.to_vec(), vec![7, 2, 3]);
+    }
+
+    #[test]
+    fn move_to_front_relinks() {
+        let mut list = LinkedList::new();
+        assert!(!list.move_to_front(|_| true));
+        for i in 1..=4 {
+            list.push_back(i);
+        }
+
+        assert!(list.move_to_front(|&x| x == 3));
+        assert!(list.move_to_front(|&x| x == 3));
+        assert!(!list.move_to_front(|&x| x == 5));
+        {
+            assert_eq!(list.to_vec(), vec![3, 1, 2, 4]);
+        }
+
+        assert!(list.move_to_front(|&x| x == 4));
+        assert_eq!(list.pop_back(), Some(2));
+        assert_eq!(
+            list.iter_mut().rev().map(|x| *x).collect::<Vec<_>>(),
+            vec![1, 3, 4]
+        );
+    }
+
+    #[test]
+    fn no_leaks() {
+        use crate::alloc_counter::assert_no_leak;
+
+        assert_no_leak(|| {
+            let list = LinkedList::new();
+            for i in 0..200 {
+                list.push_back(vec![i]);
+                list.push_front(vec![i]);
+            }
+            for _ in 0..150 {
+                list.pop_front();
+                list.pop_back();
+            }
+        });
+
+        assert_no_leak(|| {
+            let mut list = LinkedList::new();
+            {
+                let guard = list.guard();
+                for i in 0..10 {
+                    let handle = list.push_back_return_ref(vec![i], &guard);
+                    if i % 3 == 0 {
+                        list.remove(handle);
+                    }
+                }
+            }
+            list.compact();
+            let mut back = list.split_after(&vec![4]).unwrap();
+            let front = back.take_front(2);
+            back.move_to_front(|v| v[0] == 8);
+            list.clear();
+            drop((front, back));
+        });
+
+        assert_no_leak(|| {
+            let list = LinkedList::new();
+            let guard = list.guard();
+            let handle = list.push_back_return_ref(String::from("tombstone"), &guard);
+            list.push_back(String::from("live"));
+            list.remove(handle);
+        });
+    }
+
+    #[test]
+    fn flatten_nested() {
+        let nested = LinkedList::new();
+        for chunk in [&[1, 2][..], &[], &[3], &[4, 5]] {
+            let list = LinkedList::new();
+            for &i in chunk {
+                list.push_back(i);
+            }
+            nested.push_back(list);
+        }
+
+        let mut flat = nested.flatten();
+        assert_eq!(flat.len(), 5);
+        assert_eq!(flat.max_len_observed(), 5);
+        assert_eq!(
+            flat.iter_mut().rev().map(|x| *x).collect::<Vec<_>>(),
+            vec![5, 4, 3, 2, 1]
+        );
+        assert_eq!(flat.to_vec(), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn drain_into_channel() {
+        let list = LinkedList::new();
+        for i in 1..=3 {
+            list.push_back(i);
+        }
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        list.drain_to_channel(tx);
+        assert!(list.is_empty());
+        assert_eq!(rx.iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+
+        list.push_back(4);
+        let (tx, rx) = std::sync::mpsc::channel();
+        drop(rx);
+        list.drain_to_channel(tx);
+        assert_eq!(list.pop_front(), Some(4));
+    }
+
+    #[test]
+    fn snapshot_reads_by_index() {
+        let mut list = LinkedList::new();
+        for i in 1..=3 {
+            list.push_back(i.to_string());
+        }
+
+        let snapshot = list.snapshot();
+        assert_eq!(snapshot.len(), 3);
+        assert_eq!(snapshot.get(0).map(String::as_str), Some("1"));
+        assert_eq!(snapshot.get(3), None);
+        assert_eq!(snapshot.iter().collect::<Vec<_>>(), ["1", "2", "3"]);
+
+        assert_eq!(list.pop_front().as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn multiset_equality() {
+        let from = |values: &[i32]| {
+            let list = LinkedList::new();
+            for &i in values {
+                list.push_back(i);
+            }
+            list
+        };
+        let eq = |mut a: LinkedList<i32>, mut b: LinkedList<i32>| a.eq_multiset(&mut b);
+
+        assert!(eq(from(&[1, 2, 2, 3]), from(&[3, 2, 1, 2])));
+        assert!(!eq(from(&[1, 2, 3]), from(&[1, 2, 2])));
+        assert!(!eq(from(&[1, 2, 2]), from(&[1, 2])));
+        assert!(eq(from(&[]), from(&[])));
+    }
+
+    #[test]
+    fn try_push_back_gives_value_back() {
+        use crate::alloc_counter::fail_allocations;
+
+        let list = LinkedList::new();
+        assert_eq!(list.try_push_back(1), Ok(()));
+        assert_eq!(fail_allocations(|| list.try_push_back(2)), Err(2));
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_front(), None);
+    }
+
+    #[test]
+    fn interleave_alternates() {
+        let from = |values: &[i32]| {
+            let list = LinkedList::new();
+            for &i in values {
+                list.push_back(i);
+            }
+            list
+        };
+
+        let mut merged = LinkedList::interleave(from(&[1, 3, 5]), from(&[2, 4]));
+        assert_eq!(merged.len(), 5);
+        assert_eq!(
+            merged.iter_mut().rev().map(|x| *x).collect::<Vec<_>>(),
+            vec![5, 4, 3, 2, 1]
+        );
+        {
+            assert_eq!(merged.to_vec(), vec![1, 2, 3, 4, 5]);
+        }
+
+        let merged = LinkedList::interleave(from(&[]), from(&[1, 2]));
+        assert_eq!(merged.len(), 2);
+    }
+
+    #[test]
+    fn retain_counts_removed() {
+        let mut list = LinkedList::new();
+        assert_eq!(list.retain_counted(|_| false), 0);
+        for i in 0..10 {
+            list.push_back(i);
+        }
+
+        assert_eq!(list.retain_counted(|&x| x % 2 == 0), 5);
+        assert_eq!(list.len(), 5);
+        {
+            assert_eq!(list.to_vec(), vec![0, 2, 4, 6, 8]);
+        }
+
+        list.retain(|&x| x == 4);
+        assert_eq!(
+            list.iter_mut().rev().map(|x| *x).collect::<Vec<_>>(),
+            vec![4]
+        );
+        list.retain(|_| false);
+        assert!(list.is_empty());
+        assert!(list.anchor.get_mut().is_null());
+    }
+
+    #[test]
+    fn pop_front_into_array() {
+        let list = LinkedList::new();
+        for i in 0..10 {
+            list.push_back(i);
+        }
+
+        let mut out = [MaybeUninit::<i32>::uninit(); 4];
+        assert_eq!(list.pop_front_into_slice(&mut out), 4);
+        let popped: Vec<_> = out
+            .iter()
+            .map(|slot| unsafe { slot.assume_init() })
+            .collect();
+        assert_eq!(popped, vec![0, 1, 2, 3]);
+        assert_eq!(list.len(), 6);
+
+        assert_eq!(list.pop_front_into_slice(&mut out), 4);
+        assert_eq!(list.pop_front_into_slice(&mut out), 2);
+        assert_eq!(unsafe { out[1].assume_init() }, 9);
+        assert_eq!(list.pop_front_into_slice(&mut out), 0);
+    }
+
+    #[test]
+    fn pop_front_or_insert_value() {
+        let mut list = LinkedList::new();
+        assert_eq!(list.pop_front_or_insert(|| 7), 7);
+        assert!(list.is_empty());
+        assert!(list.to_vec().is_empty());
+
+        list.push_back(1);
+        list.push_back(2);
+        assert_eq!(list.pop_front_or_insert(|| unreachable!()), 1);
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.pop_front(), Some(2));
+        assert_eq!(list.pop_front(), None);
+    }
+
+    #[test]
+    fn pop_front_or_insert_races_pushes() {
+        use std::sync::Mutex;
+
+        let list = LinkedList::new();
+        let popped = Mutex::new(Vec::new());
+        thread::scope(|s| {
+            s.spawn(|| {
+                for i in 0..ITER as i64 {
+                    list.push_back(i);
+                }
+            });
+            for _ in 0..2 {
+                s.spawn(|| {
+                    for _ in 0..ITER {
+                        let t = list.pop_front_or_insert(|| -1);
+                        if t >= 0 {
+                            popped.lock().unwrap().push(t);
+                        }
+                    }
+                });
+            }
+        });
+
+        let mut seen = popped.into_inner().unwrap();
+        seen.extend(list.into_vec());
+        seen.sort_unstable();
+        assert_eq!(seen, (0..ITER as i64).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn nth_element() {
+        struct NotClone(i32);
+
+        let mut list = LinkedList::new();
+        for i in 1..=3 {
+            list.push_back(NotClone(i));
+        }
+
+        assert_eq!(list.nth(0).map(|t| t.0), Some(1));
+        assert_eq!(list.nth(2).map(|t| t.0), Some(3));
+        assert!(list.nth(3).is_none());
+        assert!(list.nth(usize::MAX).is_none());
+    }
+
+    #[test]
+    fn force_reclaim_frees_retired_nodes() {
+        use crate::alloc_counter::live_bytes;
+
+        const POPS: usize = 20;
+        let mut list = LinkedList::new();
+        for i in 0..POPS as u64 * 2 {
+            list.push_back([i; 16]);
+        }
+        for _ in 0..POPS {
+            list.pop_front();
+        }
+
+        let before = live_bytes();
+        list.force_reclaim();
+        let freed = before - live_bytes();
+        assert!(freed >= (POPS * mem::size_of::<Linked<Node<[u64; 16]>>>()) as isize);
+
+        assert_eq!(list.len(), POPS);
+        assert_eq!(list.pop_front(), Some([POPS as u64; 16]));
+        list.push_back([0; 16]);
+    }
+
+    #[test]
+    fn drain_front_lazily() {
+        let list = LinkedList::new();
+        for i in 0..10 {
+            list.push_back(i);
+        }
+
+        let mut drain = list.drain_front(5);
+        assert_eq!(drain.next(), Some(0));
+        assert_eq!(list.len(), 9);
+        assert_eq!(drain.next(), Some(1));
+        drop(drain);
+        assert_eq!(list.len(), 8);
+
+        assert_eq!(list.drain_front(100).count(), 8);
+        assert_eq!(list.drain_front(1).next(), None);
+    }
+
+    #[test]
+    fn reverse_range_window() {
+        let mut list = LinkedList::new();
+        for i in 1..=5 {
+            list.push_back(i);
+        }
+        let contents = |list: &mut LinkedList<i32>| {
+            let forward = list.iter_mut().map(|x| *x).collect::<Vec<_>>();
+            let mut backward = list.iter_mut().rev().map(|x| *x).collect::<Vec<_>>();
+            backward.reverse();
+            assert_eq!(forward, backward);
+            forward
+        };
+
+        list.reverse_range(1, 4);
+        assert_eq!(contents(&mut list), vec![1, 4, 3, 2, 5]);
+        list.reverse_range(2, 2);
+        list.reverse_range(0, 1);
+        assert_eq!(contents(&mut list), vec![1, 4, 3, 2, 5]);
+        list.reverse_range(0, 5);
+        assert_eq!(contents(&mut list), vec![5, 2, 3, 4, 1]);
+        list.reverse_range(3, 5);
+        assert_eq!(contents(&mut list), vec![5, 2, 3, 1, 4]);
+        list.reverse_range(0, 2);
+        assert_eq!(contents(&mut list), vec![2, 5, 3, 1, 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn reverse_range_out_of_bounds() {
+        let mut list = LinkedList::new();
+        list.push_back(1);
+        list.reverse_range(0, 2);
+    }
+
+    #[test]
+    fn count_matches_len_when_settled() {
+        let list = LinkedList::new();
+        thread::scope(|s| {
+            for t in 0..4 {
+                let list = &list;
+                s.spawn(move || {
+                    for i in 0..ITER {
+                        list.push_back(i);
+                        if (i + t) % 3 == 0 {
+                            list.pop_front();
+                        }
+                    }
+                });
+            }
+        });
+
+        let guard = list.guard();
+        assert_eq!(list.count(&guard), list.len());
+        let handle = list.push_back_return_ref(0, &guard);
+        list.remove(handle);
+        assert_eq!(list.count(&guard), list.len());
+    }
+
+    #[test]
+    fn count_distinct_values() {
+        let mut list = LinkedList::new();
+        for i in [1, 2, 2, 3, 3, 3] {
+            list.push_back(i);
+        }
+        assert_eq!(list.count_distinct(), 3);
+    }
+
+    #[test]
+    fn sortedness() {
+        let mut list = LinkedList::new();
+        assert!(list.is_sorted());
+        list.push_back(1);
+        assert!(list.is_sorted());
+        list.push_back(2);
+        list.push_back(3);
+        assert!(list.is_sorted());
+
+        list.pop_back();
+        list.pop_back();
+        list.push_back(3);
+        list.push_back(2);
+        assert!(!list.is_sorted());
+    }
+
+    #[test]
+    fn cursor_mut_remove_and_replace() {
+        let mut list = LinkedList::new();
+        for i in 0..5 {
+            list.push_back(i);
+        }
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(2));
+        assert_eq!(cursor.current(), Some(&mut 3));
+        assert_eq!(cursor.replace(30), Some(3));
+        cursor.move_prev();
+        assert_eq!(cursor.remove_current(), Some(1));
+        assert_eq!(cursor.current(), Some(&mut 30));
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(4));
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.replace(9), None);
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 0));
+        assert_eq!(list.len(), 2);
+        assert_eq!(list.iter_mut().map(|t| *t).collect::<Vec<_>>(), [0, 30]);
+    }
+
+    #[test]
+    fn raw_round_trip() {
+        use crate::alloc_counter::assert_no_leak;
+
+        assert_no_leak(|| {
+            let list = LinkedList::new();
+            {
+                let guard = list.guard();
+                for i in 0..5 {
+                    let handle = list.push_back_return_ref(Box::new(i), &guard);
+                    if i == 2 {
+                        list.remove(handle);
+                    }
+                }
+            }
+            list.pop_front();
+
+            let raw = list.into_raw();
+            assert_eq!(raw.len(), 3);
+            let list = unsafe { LinkedList::from_raw(raw) };
+            #[cfg(not(feature = "striped-len"))]
+            assert_eq!(list.max_len_observed(), 4);
+            let drained: Vec<_> = list.drain_front(usize::MAX).map(|t| *t).collect();
+            assert_eq!(drained, [1, 3, 4]);
+            assert!(list.is_empty());
+        });
+    }
+
+    #[test]
+    fn detach_across_threads() {
+        let list = LinkedList::new();
+        {
+            let guard = list.guard();
+            for i in 0..5 {
+                let handle = list.push_back_return_ref(i.to_string(), &guard);
+                if i == 2 {
+                    list.remove(handle);
+                }
+            }
+        }
+        list.pop_front();
+
+        let detached = list.detach();
+        assert_eq!(detached.len(), 3);
+        let mut list = thread::spawn(move || detached.into_list()).join().unwrap();
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.iter_owned().collect::<Vec<_>>(), ["1", "3", "4"]);
+        list.push_front("0".to_string());
+        assert_eq!(list.pop_back().as_deref(), Some("4"));
+    }
+
+    #[test]
+    fn group_by_runs() {
+        let list = LinkedList::new();
+        for i in [1, 1, 2, 3, 3] {
+            list.push_back(i);
+        }
+        let groups: Vec<Vec<_>> = list
+            .group_by(|&t| t)
+            .drain_front(usize::MAX)
+            .map(|group| group.drain_front(usize::MAX).collect())
+            .collect();
+        assert_eq!(groups, [vec![1, 1], vec![2], vec![3, 3]]);
+        assert!(LinkedList::<i32>::new().group_by(|&t| t).is_empty());
+    }
+
+    #[test]
+    fn set_len_repairs_drift() {
+        let list = LinkedList::new();
+        for i in 0..4 {
+            list.push_back(i);
+        }
+        list.len.fetch_add(3, RELAXED);
+        assert_eq!(list.len(), 7);
+
+        let guard = list.guard();
+        unsafe { list.set_len(list.count(&guard)) };
+        assert_eq!(list.len(), 4);
+        assert_eq!(list.len(), list.count(&guard));
+    }
+
+    #[test]
+    fn len_stays_exact_under_contention() {
+        const THREADS: usize = 8;
+        let list = LinkedList::new();
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                let list = &list;
+                s.spawn(move || {
+                    for i in 0..ITER as usize {
+                        list.push_back(i);
+                        if (i + t) % 3 == 0 {
+                            list.pop_front();
+                        }
+                    }
+                });
+            }
+        });
+        let guard = list.guard();
+        assert_eq!(list.len(), list.count(&guard));
+    }
+
+    #[test]
+    fn len_exact_for_any_concurrency_hint() {
+        const THREADS: usize = 8;
+        for hint in [1, usize::MAX] {
+            let list = LinkedList::with_concurrency_hint(hint);
+            thread::scope(|s| {
+                for t in 0..THREADS {
+                    let list = &list;
+                    s.spawn(move || {
+                        for i in 0..ITER as usize {
+                            list.push_front(i);
+                            if (i + t) % 2 == 0 {
+                                list.pop_back();
+                            }
+                        }
+                    });
+                }
+            });
+            let guard = list.guard();
+            assert_eq!(list.len(), list.count(&guard));
+        }
+    }
+
+    #[test]
+    fn linear_search_sorted() {
+        let mut list = LinkedList::new();
+        for i in [1, 3, 5] {
+            list.push_back(i);
+        }
+        assert_eq!(list.linear_search(&3), Ok(1));
+        assert_eq!(list.linear_search(&4), Err(2));
+        assert_eq!(list.linear_search(&0), Err(0));
+        assert_eq!(list.linear_search(&6), Err(3));
+    }
+
+    #[test]
+    fn try_for_each_stops_at_error() {
+        let mut list = LinkedList::new();
+        for i in [1, 2, 3, 4] {
+            list.push_back(i);
+        }
+        let mut seen = Vec::new();
+        let result = list.try_for_each(|&t| {
+            seen.push(t);
+            if t > 2 {
+                Err(t)
+            } else {
+                Ok(())
+            }
+        });
+        assert_eq!(result, Err(3));
+        assert_eq!(seen, [1, 2, 3]);
+        assert_eq!(list.try_for_each(|_| Ok::<_, ()>(())), Ok(()));
+    }
+
+    #[test]
+    fn atomic_batch_is_contiguous() {
+        const BATCH: i32 = 10;
+        let mut list = LinkedList::new();
+        let done = AtomicBool::new(false);
+        // a single consumer popping while the batch lands must still see it
+        // as one run
+        let mut all = thread::scope(|s| {
+            for _ in 0..2 {
+                s.spawn(|| {
+                    for i in 0..ITER as i32 {
+                        list.push_back(i);
+                    }
+                });
+            }
+            s.spawn(|| {
+                let batch: Vec<_> = (1000..1000 + BATCH).collect();
+                assert_eq!(list.push_back_atomic_batch(batch), Ok(()));
+                done.store(true, Ordering::Release);
+            });
+            s.spawn(|| {
+                let mut popped = Vec::new();
+                while !done.load(Ordering::Acquire) {
+                    popped.extend(list.pop_front());
+                }
+                popped
+            })
+            .join()
+            .unwrap()
+        });
+        let popped = all.len();
+
+        all.extend(list.iter().copied());
+        let start = all.iter().position(|&t| t == 1000).unwrap();
+        assert_eq!(
+            all[start..start + BATCH as usize],
+            (1000..1000 + BATCH).collect::<Vec<_>>()
+        );
+        assert_eq!(popped + list.len(), 2 * ITER as usize + BATCH as usize);
+        assert_eq!(list.push_back_atomic_batch(Vec::new()), Ok(()));
+    }
+
+    #[test]
+    fn atomic_batch_gives_items_back() {
+        use crate::alloc_counter::fail_allocations;
+
+        let list = LinkedList::new();
+        list.push_back(0);
+        let batch = vec![1, 2, 3];
+        assert_eq!(
+            fail_allocations(|| list.push_back_atomic_batch(batch)),
+            Err(vec![1, 2, 3])
+        );
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.drain_front(usize::MAX).collect::<Vec<_>>(), [0]);
+    }
+
+    #[test]
+    fn atomic_batch_fails_partway() {
+        use crate::alloc_counter::{assert_no_leak, fail_allocations_after};
+
+        for built in 1..4 {
+            assert_no_leak(|| {
+                let list = LinkedList::new();
+                let batch: Vec<_> = (1..=4).map(|i| i.to_string()).collect();
+                let back = fail_allocations_after(built, || list.push_back_atomic_batch(batch))
+                    .unwrap_err();
+                assert_eq!(back, ["1", "2", "3", "4"]);
+                assert!(list.is_empty());
+            });
+        }
+    }
+
+    #[test]
+    fn iter_owned_outlives_list() {
+        let mut list = LinkedList::new();
+        for i in [1, 2, 3] {
+            list.push_back(i);
+        }
+        let owned = list.iter_owned();
+        drop(list);
+        assert_eq!(owned.collect::<Vec<_>>(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn clear_copy_elements() {
+        use crate::alloc_counter::assert_no_leak;
+
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        struct Point {
+            x: i32,
+            y: i32,
+        }
+
+        assert_no_leak(|| {
+            let mut list = LinkedList::new();
+            for i in 0..10 {
+                list.push_back(Point { x: i, y: -i });
+            }
+            list.clear();
+            assert!(list.is_empty());
+            list.push_back(Point { x: 1, y: 2 });
+            assert_eq!(list.pop_front(), Some(Point { x: 1, y: 2 }));
+            list.push_back(Point { x: 3, y: 4 });
+        });
+    }
+
+    #[test]
+    fn replace_at_index() {
+        let mut list = LinkedList::new();
+        for i in [1, 2, 3] {
+            list.push_back(i);
+        }
+        assert_eq!(list.replace_at(1, 9), Some(2));
+        assert_eq!(list.replace_at(3, 0), None);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.iter_owned().collect::<Vec<_>>(), [1, 9, 3]);
+    }
+
+    #[test]
+    fn collect_matching_evens() {
+        let mut list = LinkedList::new();
+        for i in 0..6 {
+            list.push_back(i);
+        }
+        assert_eq!(list.collect_matching(|t| t % 2 == 0), [0, 2, 4]);
+    }
+
+    #[test]
+    fn prepend_list_keeps_order() {
+        let mut list = LinkedList::new();
+        let mut other = LinkedList::new();
+        for i in [3, 4] {
+            list.push_back(i);
+        }
+        for i in [1, 2] {
+            other.push_back(i);
+        }
+        list.prepend_list(&mut other);
+        assert!(other.is_empty());
+        assert_eq!(list.len(), 4);
+        assert_eq!(list.iter_owned().collect::<Vec<_>>(), [1, 2, 3, 4]);
+
+        let mut empty = LinkedList::new();
+        empty.prepend_list(&mut list);
+        assert_eq!(empty.iter_owned().rev().collect::<Vec<_>>(), [4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn transfer_back_to_front_keeps_order() {
+        let mut list = LinkedList::new();
+        let mut other = LinkedList::new();
+        for i in [1, 2] {
+            list.push_back(i);
+        }
+        for i in [7, 8, 9] {
+            other.push_back(i);
+        }
+        list.transfer_back_to_front(&mut other, 2);
+        assert_eq!(list.iter_owned().collect::<Vec<_>>(), [8, 9, 1, 2]);
+        assert_eq!(other.iter_owned().collect::<Vec<_>>(), [7]);
+
+        list.transfer_back_to_front(&mut other, 5);
+        assert_eq!(list.len(), 5);
+        assert!(other.is_empty());
+    }
+
+    #[test]
+    fn for_each_indexed_counts_from_zero() {
+        let mut list = LinkedList::new();
+        for i in [10, 20, 30] {
+            list.push_back(i);
+        }
+        let mut total = 0;
+        list.for_each_indexed(|i, &t| total += i * t);
+        assert_eq!(total, 80);
+    }
+
+    #[test]
+    fn min_max_by_key() {
+        let mut list = LinkedList::new();
+        assert_eq!(list.min_by_key(|t: &i32| t.abs()), None);
+        for i in [-5, 3, -1] {
+            list.push_back(i);
+        }
+        assert_eq!(list.min_by_key(|t| t.abs()), Some(&-1));
+        assert_eq!(list.max_by_key(|t| t.abs()), Some(&-5));
+    }
+
+    #[test]
+    fn swap_front_with_other() {
+        let mut a = LinkedList::new();
+        let mut b = LinkedList::new();
+        for i in [1, 2] {
+            a.push_back(i);
+        }
+        for i in [9, 8] {
+            b.push_back(i);
+        }
+        a.swap_front_with(&mut b);
+        assert_eq!(a.iter_owned().collect::<Vec<_>>(), [9, 2]);
+        assert_eq!(b.iter_owned().collect::<Vec<_>>(), [1, 8]);
+
+        let mut empty = LinkedList::new();
+        a.swap_front_with(&mut empty);
+        assert_eq!(a.iter_owned().collect::<Vec<_>>(), [9, 2]);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn drain_chunks_of_three() {
+        let mut list = LinkedList::new();
+        for i in 0..10 {
+            list.push_back(i);
+        }
+        let chunks: Vec<_> = list.drain_chunks(3).collect();
+        assert_eq!(
+            chunks,
+            [vec![0, 1, 2], vec![3, 4, 5], vec![6, 7, 8], vec![9]]
+        );
+        assert!(list.is_empty());
+        assert_eq!(list.drain_chunks(3).next(), None);
+    }
+
+    #[test]
+    fn find_then_remove() {
+        let mut list = LinkedList::new();
+        for i in [1, 2, 3, 2] {
+            list.push_back(i);
+        }
+        assert!(list.find(|&t| t == 5).is_none());
+        let handle = list.find(|&t| t == 2).unwrap();
+        assert_eq!(handle.remove(), Some(2));
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 3, 2]);
+        assert_eq!(list.len(), 3);
+    }
+
+    #[test]
+    fn retain_rev_keeps_last_three() {
+        let mut list = LinkedList::new();
+        for i in 1..=5 {
+            list.push_back(i);
+        }
+        let mut kept = 0;
+        list.retain_rev(|_| {
+            kept += 1;
+            kept <= 3
+        });
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.iter_owned().collect::<Vec<_>>(), [3, 4, 5]);
+    }
+
+    #[test]
+    fn to_arc_vec_shares() {
+        let mut list = LinkedList::new();
+        for i in [1, 2, 3] {
+            list.push_back(i);
+        }
+        let arcs = list.to_arc_vec();
+        assert_eq!(arcs.iter().map(|t| **t).collect::<Vec<_>>(), [1, 2, 3]);
+        assert!(arcs.iter().all(|t| Arc::strong_count(t) == 1));
+        let shared = arcs.clone();
+        assert!(shared.iter().all(|t| Arc::strong_count(t) == 2));
+        assert_eq!(list.len(), 3);
+    }
+
+    #[test]
+    fn cas_front_compares() {
+        let mut list = LinkedList::new();
+        assert_eq!(list.cas_front(&1, 10), Err(None));
+        for i in [1, 2, 3] {
+            list.push_back(i);
+        }
+        assert_eq!(list.cas_front(&1, 10), Ok(()));
+        assert_eq!(list.cas_front(&1, 20), Err(Some(10)));
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [10, 2, 3]);
+        assert_eq!(list.iter().rev().copied().collect::<Vec<_>>(), [3, 2, 10]);
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.pop_back(), Some(2));
+        assert_eq!(list.cas_front(&10, 11), Ok(()));
+        assert_eq!(list.pop_back(), Some(11));
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn iter_while_stops_early() {
+        let mut list = LinkedList::new();
+        for i in [1, 2, 3, 4, 1] {
+            list.push_back(i);
+        }
+        assert_eq!(list.iter_while(|&t| t < 3).collect::<Vec<_>>(), [&1, &2]);
+    }
+
+    /// Walks the list checking that every `prev` mirrors a `next`, that no
+    /// node in it has been retired, and that `len` counts the live ones.
+    fn check_integrity<T>(list: &mut LinkedList<T>) {
+        let len = *list.len.get_mut();
+        let Some((head, tail)) = list.node_ends() else {
+            assert_eq!(len, 0);
+            return;
+        };
+        let (mut current, mut prev, mut live) = (head, ptr::null_mut(), 0);
+        loop {
+            let node = unsafe { &*current };
+            #[cfg(debug_assertions)]
+            assert!(!node.retired.load(Ordering::Relaxed));
+            if current != head {
+                assert_eq!(node.prev.load(Ordering::Relaxed), prev);
+            }
+            if !node.removed.load(Ordering::Relaxed) {
+                live += 1;
+            }
+            if current == tail {
+                break;
+            }
+            prev = current;
+            current = node.next.load(Ordering::Relaxed);
+        }
+        assert_eq!(live, len);
+    }
+
+    #[test]
+    fn empty_and_refill_stays_intact() {
+        let mut list = LinkedList::new();
+        for round in 0..20 {
+            thread::scope(|s| {
+                for t in 0..2 {
+                    let list = &list;
+                    s.spawn(move || {
+                        for i in 0..ITER {
+                            if (i + t) % 2 == 0 {
+                                list.push_back(i);
+                            } else {
+                                list.push_front(i);
+                            }
+                            if i % 3 == round % 3 {
+                                list.pop_back();
+                            }
+                        }
+                    });
+                }
+            });
+            check_integrity(&mut list);
+            while list.pop_front().is_some() {}
+            check_integrity(&mut list);
+            assert!(list.is_empty());
+        }
+    }
+
+    #[test]
+    fn pop_front_coalesce_runs() {
+        let mut list = LinkedList::new();
+        for c in ['a', 'a', 'b', 'c', 'c', 'c'] {
+            list.push_back((c, 1));
+        }
+        let merge = |acc: &(char, usize), next: &(char, usize)| {
+            (acc.0 == next.0).then_some((acc.0, acc.1 + next.1))
+        };
+        assert_eq!(list.pop_front_coalesce(merge), Some(('a', 2)));
+        assert_eq!(list.pop_front_coalesce(merge), Some(('b', 1)));
+        assert_eq!(list.pop_front_coalesce(merge), Some(('c', 3)));
+        assert_eq!(list.pop_front_coalesce(merge), None);
+    }
+
+    #[test]
+    fn repair_prev_links_after_corruption() {
+        let mut list = LinkedList::new();
+        for i in 0..4 {
+            list.push_back(i);
+        }
+        let (head, tail) = list.node_ends().unwrap();
+        unsafe { &*tail }.prev.store(head, Ordering::Relaxed);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| check_integrity(&mut list)));
+        assert!(result.is_err());
+
+        list.repair_prev_links();
+        check_integrity(&mut list);
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.pop_back(), Some(2));
+    }
+
+    #[test]
+    fn pop_front_while_consumes_stopper() {
+        let list = LinkedList::new();
+        for i in [1, 2, 3, 4] {
+            list.push_back(i);
+        }
+        let mut seen = Vec::new();
+        let accepted = list.pop_front_while(|t| {
+            seen.push(t);
+            t < 3
+        });
+        assert_eq!(accepted, 2);
+        assert_eq!(seen, [1, 2, 3]);
+        assert_eq!(list.drain_front(usize::MAX).collect::<Vec<_>>(), [4]);
+        assert_eq!(list.pop_front_while(|_| true), 0);
+    }
+
+    /// Leans on the debug assertions in the push and pop paths, which check
+    /// the ends of every anchor they replace.
+    #[test]
+    fn mixed_ends_keep_links_consistent() {
+        let list = LinkedList::new();
+        thread::scope(|s| {
+            for t in 0..4 {
+                let list = &list;
+                s.spawn(move || {
+                    for i in 0..ITER {
+                        match (i + t) % 4 {
+                            0 => list.push_back(i),
+                            1 => list.push_front(i),
+                            2 => drop(list.pop_back()),
+                            _ => drop(list.pop_front()),
+                        }
+                    }
+                });
+            }
+        });
+        let guard = list.guard();
+        assert_eq!(list.len(), list.count(&guard));
+    }
+
+    #[test]
+    fn into_vec_never_reallocates() {
+        use crate::alloc_counter::reallocations;
+
+        let list = LinkedList::new();
+        for i in 0..1000 {
+            list.push_back(i);
+        }
+        let before = reallocations();
+        let vec = list.into_vec();
+        assert_eq!(reallocations(), before);
+        assert_eq!(vec.capacity(), 1000);
+        assert!(vec.into_iter().eq(0..1000));
+    }
+
+    #[test]
+    fn remove_by_value() {
+        let mut list = LinkedList::new();
+        for i in [1, 2, 2, 3] {
+            list.push_back(i);
+        }
+        assert!(list.remove_first(&2));
+        assert_eq!(list.iter_owned().collect::<Vec<_>>(), [1, 2, 3]);
+        assert!(!list.remove_first(&4));
+
+        list.push_back(2);
+        assert_eq!(list.remove_all(&2), 2);
+        assert_eq!(list.iter_owned().collect::<Vec<_>>(), [1, 3]);
+        assert_eq!(list.len(), 2);
+    }
+
+    #[test]
+    fn dedup_all_keeps_first() {
+        let mut list = LinkedList::new();
+        for i in [1, 2, 1, 3, 2] {
+            list.push_back(i);
+        }
+        list.dedup_all();
+        assert_eq!(list.iter_owned().collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(list.len(), 3);
+    }
+
+    #[test]
+    fn into_sorted_vec_sorts() {
+        let list = LinkedList::new();
+        for i in [3, 1, 2] {
+            list.push_back(i);
+        }
+        assert_eq!(list.into_sorted_vec(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn deque_view_ops() {
+        let mut list = LinkedList::new();
+        {
+            let guard = list.guard();
+            let handle = list.push_back_return_ref(0, &guard);
+            list.remove(handle);
+        }
+        let mut view = list.deque_view();
+        assert!(view.is_empty());
+        assert_eq!(view.front(), None);
+        assert_eq!(view.pop_back(), None);
+        view.push_back(2);
+        view.push_front(1);
+        view.push_back(3);
+        assert_eq!(view.front(), Some(&1));
+        assert_eq!(view.back(), Some(&3));
+        *view.front_mut().unwrap() = 10;
+        *view.back_mut().unwrap() = 30;
+        assert_eq!(view.len(), 3);
+        assert_eq!(view.pop_front(), Some(10));
+        assert_eq!(view.pop_back(), Some(30));
+        assert_eq!(view.pop_back(), Some(2));
+        assert_eq!(view.pop_front(), None);
+        view.push_front(4);
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.max_len_observed(), 3);
+        assert_eq!(list.pop_back(), Some(4));
+    }
+
+    #[test]
+    fn retain_indexed_even_positions() {
+        let mut list = LinkedList::new();
+        for i in [10, 20, 30, 40] {
+            list.push_back(i);
+        }
+        list.retain_indexed(|i, _| i % 2 == 0);
+        assert_eq!(list.iter_owned().collect::<Vec<_>>(), [10, 30]);
+    }
+
+    #[test]
+    fn len_threshold_crossings() {
+        use std::sync::Mutex;
+
+        let mut list = LinkedList::new();
+        let crossings = Arc::new(Mutex::new(Vec::new()));
+        {
+            let crossings = crossings.clone();
+            list.on_len_threshold(5, move |len| crossings.lock().unwrap().push(len));
+        }
+        for i in 0..7 {
+            list.push_back(i);
+        }
+        assert_eq!(*crossings.lock().unwrap(), [5]);
+        list.pop_front();
+        list.pop_back();
+        list.pop_back();
+        assert_eq!(*crossings.lock().unwrap(), [5, 4]);
+    }
+
+    #[test]
+    fn zip_stops_at_shorter() {
+        let numbers = LinkedList::new();
+        for i in [1, 2, 3] {
+            numbers.push_back(i);
+        }
+        let letters = LinkedList::new();
+        for c in ['a', 'b'] {
+            letters.push_back(c);
+        }
+        let mut zipped = numbers.zip(letters);
+        assert_eq!(
+            zipped.iter_owned().collect::<Vec<_>>(),
+            [(1, 'a'), (2, 'b')]
+        );
+    }
+
+    #[test]
+    fn unzip_pairs() {
+        let pairs = LinkedList::new();
+        for pair in [(1, 'a'), (2, 'b')] {
+            pairs.push_back(pair);
+        }
+        let (mut numbers, mut letters) = pairs.unzip();
+        assert_eq!(numbers.iter_owned().collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(letters.iter_owned().collect::<Vec<_>>(), ['a', 'b']);
+    }
+
+    #[test]
+    fn shard_by_remainder() {
+        let list = LinkedList::new();
+        for i in 0..10 {
+            list.push_back(i);
+        }
+        let shards: Vec<Vec<_>> = list
+            .shard_by(3, |&x| x % 3)
+            .into_iter()
+            .map(LinkedList::into_vec)
+            .collect();
+        assert_eq!(shards, [vec![0, 3, 6, 9], vec![1, 4, 7], vec![2, 5, 8]]);
+    }
+
+    #[test]
+    fn trim_front_while_drops_promptly() {
+        struct Counted {
+            value: i32,
+            drops: Arc<AtomicUsize>,
+        }
+
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                self.drops.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let mut list = LinkedList::new();
+        for value in [1, 2, 3, 10, 4] {
+            list.push_back(Counted {
+                value,
+                drops: drops.clone(),
+            });
+        }
+        assert_eq!(list.trim_front_while(|t| t.value < 5), 3);
+        assert_eq!(drops.load(Ordering::Relaxed), 3);
+        assert_eq!(list.len(), 2);
+        assert_eq!(list.peek_front_mut().map(|t| t.value), Some(10));
+
+        assert_eq!(list.trim_front_while(|_| true), 2);
+        assert_eq!(drops.load(Ordering::Relaxed), 5);
+        assert!(list.is_empty());
+    }
+
+    mod props {
+        use proptest::prelude::*;
+        use proptest::test_runner::TestCaseError;
+
+        use super::*;
+
+        #[derive(Debug, Clone)]
+        enum Op {
+            PushBack(i32),
+            PushFront(i32),
+            PopFront,
+            PopBack,
+        }
+
+        /// Applies `ops` to a list and a `VecDeque`, failing on the first
+        /// step where they disagree.
+        fn matches_vec_deque(ops: &[Op]) -> Result<(), TestCaseError> {
+            let mut list = LinkedList::new();
+            let mut model = VecDeque::new();
+            for op in ops {
+                match *op {
+                    Op::PushBack(i) => {
+                        list.push_back(i);
+                        model.push_back(i);
+                    }
+                    Op::PushFront(i) => {
+                        list.push_front(i);
+                        model.push_front(i);
+                    }
+                    Op::PopFront => prop_assert_eq!(list.pop_front(), model.pop_front()),
+                    Op::PopBack => prop_assert_eq!(list.pop_back(), model.pop_back()),
+                }
+                prop_assert_eq!(list.len(), model.len());
+            }
+            prop_assert!(list.iter().eq(model.iter()));
+            Ok(())
+        }
+
+        proptest! {
+            #[test]
+            fn fifo(ops in prop::collection::vec(prop_oneof![
+                any::<i32>().prop_map(Op::PushBack),
+                Just(Op::PopFront),
+            ], 0..200)) {
+                matches_vec_deque(&ops)?;
+            }
+
+            #[test]
+            fn lifo_from_front(ops in prop::collection::vec(prop_oneof![
+                any::<i32>().prop_map(Op::PushFront),
+                Just(Op::PopFront),
+            ], 0..200)) {
+                matches_vec_deque(&ops)?;
+            }
+
+            #[test]
+            fn both_ends(ops in prop::collection::vec(prop_oneof![
+                any::<i32>().prop_map(Op::PushBack),
+                any::<i32>().prop_map(Op::PushFront),
+                Just(Op::PopFront),
+                Just(Op::PopBack),
+            ], 0..200)) {
+                matches_vec_deque(&ops)?;
+            }
+        }
     }
 }
@@ -0,0 +1,116 @@
+use std::hint;
+use std::sync::atomic::{self, AtomicUsize};
+use std::sync::Mutex;
+use std::thread::{self, Thread};
+
+use super::ordering::{ACQUIRE, RELEASE, SEQ_CST};
+use super::LinkedList;
+
+/// A [`LinkedList`](super::LinkedList) whose consumers can wait for a push,
+/// with [`pop_front_blocking`](Self::pop_front_blocking).
+///
+/// Waking parked consumers costs every push through the wrapper a `SeqCst`
+/// fence, which is why this is a separate type instead of part of the list.
+/// Only pushes made through the wrapper wake anybody.
+pub struct BlockingList<T> {
+    list: LinkedList<T>,
+    /// Threads parked in `pop_front_blocking`, and how many of them there
+    /// are, so pushes can skip the lock.
+    pub(super) waiters: Mutex<Vec<Thread>>,
+    sleepers: AtomicUsize,
+}
+
+impl<T> BlockingList<T> {
+    pub fn new() -> Self {
+        Self::from(LinkedList::new())
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    pub fn push_back(&self, t: T) {
+        self.list.push_back(t);
+        self.wake_waiters();
+    }
+
+    pub fn push_front(&self, t: T) {
+        self.list.push_front(t);
+        self.wake_waiters();
+    }
+
+    pub fn pop_front(&self) -> Option<T> {
+        self.list.pop_front()
+    }
+
+    pub fn pop_back(&self) -> Option<T> {
+        self.list.pop_back()
+    }
+
+    /// Pops the front, waiting for a push if the list is empty. Retries up to
+    /// `spin_limit` times first, then parks the thread until the next push.
+    pub fn pop_front_blocking(&self, spin_limit: usize) -> T {
+        for _ in 0..spin_limit {
+            if let Some(t) = self.list.pop_front() {
+                return t;
+            }
+            hint::spin_loop();
+        }
+        let me = thread::current();
+        loop {
+            self.waiters.lock().unwrap().push(me.clone());
+            self.sleepers.fetch_add(1, SEQ_CST);
+            // pairs with the fence in `wake_waiters`: either this pop sees the
+            // push, or the push sees this thread waiting
+            atomic::fence(SEQ_CST);
+            let popped = self.list.pop_front();
+            if popped.is_none() {
+                thread::park();
+            }
+            self.waiters
+                .lock()
+                .unwrap()
+                .retain(|waiter| waiter.id() != me.id());
+            self.sleepers.fetch_sub(1, RELEASE);
+            if let Some(t) = popped {
+                return t;
+            }
+        }
+    }
+
+    /// The list underneath, without the waiting.
+    pub fn into_inner(self) -> LinkedList<T> {
+        self.list
+    }
+
+    #[inline]
+    fn wake_waiters(&self) {
+        atomic::fence(SEQ_CST);
+        if self.sleepers.load(ACQUIRE) == 0 {
+            return;
+        }
+        for waiter in self.waiters.lock().unwrap().drain(..) {
+            waiter.unpark();
+        }
+    }
+}
+
+impl<T> Default for BlockingList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<LinkedList<T>> for BlockingList<T> {
+    fn from(list: LinkedList<T>) -> Self {
+        Self {
+            list,
+            waiters: Mutex::new(Vec::new()),
+            sleepers: AtomicUsize::new(0),
+        }
+    }
+}
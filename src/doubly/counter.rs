@@ -0,0 +1,122 @@
+//! The list's `len` counter.
+//!
+//! By default it is a single atomic. With the `striped-len` feature it is
+//! split into cache-line padded stripes, one per group of threads, so pushes
+//! and pops from different threads don't fight over the same line. Reads
+//! sum every stripe instead, and updates only touch their own stripe, so the
+//! list only learns the total when it asks for it. A list built with a low
+//! concurrency hint can still opt out and keep everything in the first
+//! stripe.
+
+#[cfg(not(feature = "striped-len"))]
+mod imp {
+    pub type Len = std::sync::atomic::AtomicUsize;
+}
+
+#[cfg(feature = "striped-len")]
+mod imp {
+    use std::array;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::super::ordering::RELAXED;
+
+    const STRIPES: usize = 16;
+
+    /// The fewest expected threads a concurrency hint needs to turn striping
+    /// on. A guess: on fewer cores summing the stripes costs more than the
+    /// contention it saves.
+    pub const MIN_STRIPED_THREADS: usize = 4;
+
+    static NEXT_STRIPE: AtomicUsize = AtomicUsize::new(0);
+
+    thread_local! {
+        static STRIPE: usize = NEXT_STRIPE.fetch_add(1, RELAXED) % STRIPES;
+    }
+
+    #[repr(align(128))]
+    struct Stripe(AtomicUsize);
+
+    /// Mirrors the parts of `AtomicUsize` the list uses. A single stripe can
+    /// wrap below zero when a thread pops what another pushed; the wrapping
+    /// sum still comes out right.
+    pub struct Len {
+        stripes: [Stripe; STRIPES],
+        striped: bool,
+    }
+
+    impl Len {
+        pub fn new(len: usize) -> Self {
+            Self::with_striping(len, true)
+        }
+
+        /// Without striping every thread uses the first stripe, so reads
+        /// only have one to look at.
+        pub fn with_striping(len: usize, striped: bool) -> Self {
+            Self {
+                stripes: array::from_fn(|i| Stripe(AtomicUsize::new(if i == 0 { len } else { 0 }))),
+                striped,
+            }
+        }
+
+        /// The stripes are read one after another, so a pop can be counted
+        /// before the push it took; such a sum below zero reads as 0.
+        pub fn load(&self, order: Ordering) -> usize {
+            if !self.striped {
+                return self.stripes[0].0.load(order);
+            }
+            let sum = self
+                .stripes
+                .iter()
+                .fold(0, |sum: usize, stripe| sum.wrapping_add(stripe.0.load(order)));
+            if (sum as isize) < 0 {
+                0
+            } else {
+                sum
+            }
+        }
+
+        /// Returns what this thread's stripe held before the addition, which
+        /// is only the total when striping is off.
+        pub fn fetch_add(&self, n: usize, order: Ordering) -> usize {
+            self.local().fetch_add(n, order)
+        }
+
+        /// Returns what this thread's stripe held before the subtraction,
+        /// like `fetch_add`.
+        pub fn fetch_sub(&self, n: usize, order: Ordering) -> usize {
+            self.local().fetch_sub(n, order)
+        }
+
+        /// Whether `fetch_add` and `fetch_sub` return stripes rather than the
+        /// total.
+        pub fn is_striped(&self) -> bool {
+            self.striped
+        }
+
+        /// Not atomic as a whole: an update racing with it may be lost.
+        pub fn store(&self, len: usize, order: Ordering) {
+            for (i, stripe) in self.stripes.iter().enumerate() {
+                stripe.0.store(if i == 0 { len } else { 0 }, order);
+            }
+        }
+
+        /// Folds every stripe into the first one and hands that out.
+        pub fn get_mut(&mut self) -> &mut usize {
+            let (first, rest) = self.stripes.split_first_mut().unwrap();
+            for stripe in rest {
+                let n = std::mem::take(stripe.0.get_mut());
+                *first.0.get_mut() = first.0.get_mut().wrapping_add(n);
+            }
+            first.0.get_mut()
+        }
+
+        fn local(&self) -> &AtomicUsize {
+            if !self.striped {
+                return &self.stripes[0].0;
+            }
+            &self.stripes[STRIPE.with(|stripe| *stripe)].0
+        }
+    }
+}
+
+pub use imp::*;
@@ -0,0 +1,72 @@
+use seize::Linked;
+use std::mem::{self, ManuallyDrop};
+use std::ptr;
+use std::sync::atomic::Ordering;
+
+use super::{LinkedList, NodePtr};
+
+/// Editing cursor over an exclusively borrowed list, returned by
+/// [`LinkedList::cursor_front_mut`](super::LinkedList::cursor_front_mut).
+///
+/// Past the back the cursor sits on a "ghost" position with no element;
+/// moving on from there wraps around to the front, and back again.
+pub struct CursorMut<'a, T> {
+    list: &'a mut LinkedList<T>,
+    current: NodePtr<T>,
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    pub(super) fn new(list: &'a mut LinkedList<T>) -> Self {
+        // with removed nodes gone every link leads to an element
+        list.retain(|_| true);
+        let current = list.node_ends().map_or(ptr::null_mut(), |(head, _)| head);
+        Self { list, current }
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        let node = unsafe { self.current.as_mut()? };
+        Some(&mut node.inner)
+    }
+
+    pub fn move_next(&mut self) {
+        let Some((head, tail)) = self.list.node_ends() else {
+            return;
+        };
+        self.current = match self.current {
+            current if current.is_null() => head,
+            current if current == tail => ptr::null_mut(),
+            current => unsafe { &*current }.next.load(Ordering::Relaxed),
+        };
+    }
+
+    pub fn move_prev(&mut self) {
+        let Some((head, tail)) = self.list.node_ends() else {
+            return;
+        };
+        self.current = match self.current {
+            current if current.is_null() => tail,
+            current if current == head => ptr::null_mut(),
+            current => unsafe { &*current }.prev.load(Ordering::Relaxed),
+        };
+    }
+
+    /// Unlinks the element under the cursor and moves on to the next one.
+    /// Returns `None` on the ghost position.
+    pub fn remove_current(&mut self) -> Option<T> {
+        if self.current.is_null() {
+            return None;
+        }
+        let node = self.current;
+        self.move_next();
+        self.list.unlink_node(node);
+        *self.list.len.get_mut() -= 1;
+        let node = Linked::into_inner(*unsafe { Box::from_raw(node) });
+        Some(ManuallyDrop::into_inner(node.inner))
+    }
+
+    /// Puts `value` in place of the element under the cursor, returning the
+    /// old one. Returns `None`, dropping `value`, on the ghost position.
+    pub fn replace(&mut self, value: T) -> Option<T> {
+        Some(mem::replace(self.current()?, value))
+    }
+}
@@ -0,0 +1,143 @@
+use seize::Linked;
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+use std::ptr;
+use std::sync::atomic::Ordering;
+
+use super::{Anchor, Node};
+
+/// Iterator returned by [`LinkedList::iter`](super::LinkedList::iter).
+pub struct Iter<'g, T> {
+    anchor: Option<&'g Anchor<T>>,
+    front: *mut Linked<Node<T>>,
+    back: *mut Linked<Node<T>>,
+}
+
+impl<'g, T> Iter<'g, T> {
+    pub(super) fn new(anchor: Option<&'g Anchor<T>>) -> Self {
+        let (front, back) = anchor.map_or((ptr::null_mut(), ptr::null_mut()), |anchor| {
+            (anchor.head, anchor.tail)
+        });
+        Self {
+            anchor,
+            front,
+            back,
+        }
+    }
+
+    /// Takes `node` out of the remaining range, which ends once both
+    /// cursors have met.
+    fn advance(&mut self, node: *mut Linked<Node<T>>, anchor: &Anchor<T>, forward: bool) {
+        if self.front == self.back {
+            self.front = ptr::null_mut();
+            self.back = ptr::null_mut();
+        } else if forward {
+            self.front = anchor.next_of(node);
+        } else {
+            self.back = anchor.prev_of(node);
+        }
+    }
+}
+
+impl<'g, T> Iter<'g, T> {
+    /// Like `next`, but yields the node itself.
+    pub(super) fn next_node(&mut self) -> Option<*mut Linked<Node<T>>> {
+        let anchor = self.anchor?;
+        while !self.front.is_null() {
+            let current = self.front;
+            self.advance(current, anchor, true);
+            if !unsafe { &*current }.is_removed() {
+                return Some(current);
+            }
+        }
+        None
+    }
+}
+
+impl<'g, T> Iterator for Iter<'g, T> {
+    type Item = &'g T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_node().map(|node| &*unsafe { &*node }.inner)
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let anchor = self.anchor?;
+        while !self.back.is_null() {
+            let current = self.back;
+            self.advance(current, anchor, false);
+            let node = unsafe { &*current };
+            if !node.is_removed() {
+                return Some(&node.inner);
+            }
+        }
+        None
+    }
+}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+/// Front to back iterator returned by [`LinkedList::iter_mut`](super::LinkedList::iter_mut).
+pub struct IterMut<'a, T> {
+    front: *mut Linked<Node<T>>,
+    back: *mut Linked<Node<T>>,
+    _marker: PhantomData<&'a mut T>,
+}
+
+impl<'a, T> IterMut<'a, T> {
+    pub(super) fn new(anchor: Option<&'a mut Anchor<T>>) -> Self {
+        let (front, back) = anchor.map_or((ptr::null_mut(), ptr::null_mut()), |anchor| {
+            (anchor.head, anchor.tail)
+        });
+        Self {
+            front,
+            back,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Takes `node` out of the remaining range. Nothing can be half way
+    /// through a push here, so every link in it can be followed as is.
+    fn advance(&mut self, node: &Node<T>, forward: bool) {
+        if self.front == self.back {
+            self.front = ptr::null_mut();
+            self.back = ptr::null_mut();
+        } else if forward {
+            self.front = node.next.load(Ordering::Relaxed);
+        } else {
+            self.back = node.prev.load(Ordering::Relaxed);
+        }
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.front.is_null() {
+            let node = unsafe { &mut *self.front };
+            self.advance(node, true);
+            if !*node.removed.get_mut() {
+                return Some(&mut node.inner);
+            }
+        }
+        None
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while !self.back.is_null() {
+            let node = unsafe { &mut *self.back };
+            self.advance(node, false);
+            if !*node.removed.get_mut() {
+                return Some(&mut node.inner);
+            }
+        }
+        None
+    }
+}
+
+impl<T> FusedIterator for IterMut<'_, T> {}
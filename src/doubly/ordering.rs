@@ -0,0 +1,33 @@
+//! Memory orderings used by the concurrent paths of the list.
+//!
+//! With the `strict-ordering` feature every one of them becomes `SeqCst`. If a
+//! bug goes away under the feature but comes back with the tuned orderings,
+//! one of them is too weak; `cargo test --features strict-ordering` runs the
+//! whole suite that way. Only atomics other threads can reach go through
+//! here: `&mut self` code has the nodes to itself and stays `Relaxed`.
+
+use std::sync::atomic::Ordering;
+
+#[cfg(not(feature = "strict-ordering"))]
+mod imp {
+    use super::Ordering;
+
+    pub const ACQUIRE: Ordering = Ordering::Acquire;
+    pub const RELEASE: Ordering = Ordering::Release;
+    pub const ACQ_REL: Ordering = Ordering::AcqRel;
+    pub const RELAXED: Ordering = Ordering::Relaxed;
+    pub const SEQ_CST: Ordering = Ordering::SeqCst;
+}
+
+#[cfg(feature = "strict-ordering")]
+mod imp {
+    use super::Ordering;
+
+    pub const ACQUIRE: Ordering = Ordering::SeqCst;
+    pub const RELEASE: Ordering = Ordering::SeqCst;
+    pub const ACQ_REL: Ordering = Ordering::SeqCst;
+    pub const RELAXED: Ordering = Ordering::SeqCst;
+    pub const SEQ_CST: Ordering = Ordering::SeqCst;
+}
+
+pub use imp::*;
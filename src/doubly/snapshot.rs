@@ -0,0 +1,34 @@
+use std::iter::FusedIterator;
+
+/// References to the elements of a list, returned by
+/// [`LinkedList::snapshot`](super::LinkedList::snapshot).
+///
+/// Reading it never walks the list again. The list stays borrowed for as long
+/// as the snapshot lives, so nothing can be pushed or popped meanwhile.
+pub struct Snapshot<'a, T> {
+    elements: Vec<&'a T>,
+}
+
+impl<'a, T> Snapshot<'a, T> {
+    pub(super) fn new(elements: Vec<&'a T>) -> Self {
+        Self { elements }
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&'a T> {
+        self.elements.get(index).copied()
+    }
+
+    pub fn iter(
+        &self,
+    ) -> impl ExactSizeIterator<Item = &'a T> + DoubleEndedIterator + FusedIterator + '_ {
+        self.elements.iter().copied()
+    }
+}
@@ -0,0 +1,88 @@
+use seize::Linked;
+use std::mem::ManuallyDrop;
+use std::sync::atomic::Ordering;
+
+use super::{LinkedList, Node, NodePtr};
+
+/// `VecDeque`-like access to an exclusively borrowed list, returned by
+/// [`LinkedList::deque_view`](super::LinkedList::deque_view).
+///
+/// Nobody else can get at the list while the view exists, so every operation
+/// is plain pointer surgery, with no guard or CAS involved.
+pub struct DequeView<'a, T> {
+    list: &'a mut LinkedList<T>,
+}
+
+impl<'a, T> DequeView<'a, T> {
+    pub(super) fn new(list: &'a mut LinkedList<T>) -> Self {
+        // with removed nodes gone both ends are always live elements
+        list.retain(|_| true);
+        Self { list }
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len.load(Ordering::Relaxed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.ends().map(|(head, _)| &*unsafe { &*head }.inner)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.ends().map(|(_, tail)| &*unsafe { &*tail }.inner)
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.list.peek_front_mut()
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.list.peek_back_mut()
+    }
+
+    pub fn push_front(&mut self, t: T) {
+        let node = self.list.collector.link_boxed(Node::new(t));
+        self.list.link_front_node(node);
+        self.grow();
+    }
+
+    pub fn push_back(&mut self, t: T) {
+        let node = self.list.collector.link_boxed(Node::new(t));
+        self.list.link_back_node(node);
+        self.grow();
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        let (head, _) = self.list.node_ends()?;
+        Some(self.take(head))
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        let (_, tail) = self.list.node_ends()?;
+        Some(self.take(tail))
+    }
+
+    fn ends(&self) -> Option<(NodePtr<T>, NodePtr<T>)> {
+        let anchor = unsafe { self.list.anchor.load(Ordering::Relaxed).as_ref()? };
+        Some((anchor.head, anchor.tail))
+    }
+
+    fn grow(&mut self) {
+        let len = self.list.len.get_mut();
+        *len += 1;
+        let len = *len;
+        let max_len = self.list.max_len.get_mut();
+        *max_len = (*max_len).max(len);
+    }
+
+    fn take(&mut self, node: NodePtr<T>) -> T {
+        self.list.unlink_node(node);
+        *self.list.len.get_mut() -= 1;
+        let node = Linked::into_inner(*unsafe { Box::from_raw(node) });
+        ManuallyDrop::into_inner(node.inner)
+    }
+}
@@ -2,7 +2,10 @@ use seize::{reclaim, AtomicPtr, Collector};
 use std::sync::atomic::Ordering;
 use std::{mem::ManuallyDrop, ptr};
 
+#[cfg(test)]
+mod alloc_counter;
 pub mod doubly;
+pub mod mpsc;
 pub mod queue;
 
 pub struct LinkedList<T> {
@@ -35,7 +38,7 @@ impl<T> LinkedList<T> {
 
         loop {
             let head = guard.protect(&self.head, Ordering::Acquire);
-            unsafe { (*new).next.store(head, Ordering::Release) }
+            unsafe { (&*new).next.store(head, Ordering::Release) }
 
             if self
                 .head
@@ -116,7 +119,7 @@ impl<T> LinkedList<T> {
                 return None;
             }
 
-            let next = unsafe { (*head).next.load(Ordering::Acquire) };
+            let next = unsafe { (&*head).next.load(Ordering::Acquire) };
 
             if self
                 .head
@@ -124,7 +127,7 @@ impl<T> LinkedList<T> {
                 .is_ok()
             {
                 unsafe {
-                    let data = ptr::read(&(*head).inner);
+                    let data = ptr::read(&(&*head).inner);
                     self.collector.retire(head, reclaim::boxed::<Node<T>>);
                     return Some(ManuallyDrop::into_inner(data));
                 }
@@ -137,6 +140,12 @@ impl<T> LinkedList<T> {
     }
 }
 
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> Drop for LinkedList<T> {
     fn drop(&mut self) {
         while self.pop_front().is_some() {}
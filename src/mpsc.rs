@@ -0,0 +1,235 @@
+//! Queues for when only one thread pops.
+//!
+//! With a single consumer the front needs no CAS: the consumer owns it
+//! outright, and a node it has moved past is never touched by producers
+//! again, so it can be freed on the spot without a collector. Producers only
+//! ever exchange the tail.
+
+use std::cell::UnsafeCell;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+struct Node<T> {
+    inner: UnsafeCell<Option<T>>,
+    next: AtomicPtr<Node<T>>,
+}
+
+impl<T> Node<T> {
+    fn boxed(t: Option<T>) -> *mut Node<T> {
+        Box::into_raw(Box::new(Node {
+            inner: UnsafeCell::new(t),
+            next: AtomicPtr::new(ptr::null_mut()),
+        }))
+    }
+}
+
+/// The consumer's end, shared by both queues. `head` is a node whose element
+/// has already been taken; the front element lives in its successor.
+struct Front<T> {
+    head: UnsafeCell<*mut Node<T>>,
+}
+
+impl<T> Front<T> {
+    fn new(stub: *mut Node<T>) -> Self {
+        Self {
+            head: UnsafeCell::new(stub),
+        }
+    }
+
+    /// # Safety
+    ///
+    /// Must not be called from two threads at once.
+    unsafe fn pop(&self) -> Option<T> {
+        let head = *self.head.get();
+        let next = (&*head).next.load(Ordering::Acquire);
+        if next.is_null() {
+            return None;
+        }
+        *self.head.get() = next;
+        drop(Box::from_raw(head));
+        (*(&*next).inner.get()).take()
+    }
+}
+
+impl<T> Drop for Front<T> {
+    fn drop(&mut self) {
+        while unsafe { self.pop() }.is_some() {}
+        drop(unsafe { Box::from_raw(*self.head.get_mut()) });
+    }
+}
+
+/// Multi-producer single-consumer FIFO queue.
+///
+/// A push is a single swap of the tail, and a pop is a plain load. A producer
+/// that is preempted between swapping the tail and linking its node hides the
+/// elements pushed after it until it resumes.
+pub struct Mpsc<T> {
+    front: Front<T>,
+    tail: AtomicPtr<Node<T>>,
+}
+
+unsafe impl<T: Send> Send for Mpsc<T> {}
+unsafe impl<T: Send> Sync for Mpsc<T> {}
+
+impl<T> Mpsc<T> {
+    pub fn new() -> Self {
+        let stub = Node::boxed(None);
+        Self {
+            front: Front::new(stub),
+            tail: AtomicPtr::new(stub),
+        }
+    }
+
+    pub fn push_back(&self, t: T) {
+        let new = Node::boxed(Some(t));
+        let prev = self.tail.swap(new, Ordering::AcqRel);
+        unsafe { &*prev }.next.store(new, Ordering::Release);
+    }
+
+    /// # Safety
+    ///
+    /// Only one thread may pop at a time.
+    pub unsafe fn pop_front(&self) -> Option<T> {
+        self.front.pop()
+    }
+}
+
+impl<T> Default for Mpsc<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Single-producer single-consumer FIFO queue.
+///
+/// Like [`Mpsc`], but the producer owns the tail too, so a push is a plain
+/// store.
+pub struct Spsc<T> {
+    front: Front<T>,
+    tail: UnsafeCell<*mut Node<T>>,
+}
+
+unsafe impl<T: Send> Send for Spsc<T> {}
+unsafe impl<T: Send> Sync for Spsc<T> {}
+
+impl<T> Spsc<T> {
+    pub fn new() -> Self {
+        let stub = Node::boxed(None);
+        Self {
+            front: Front::new(stub),
+            tail: UnsafeCell::new(stub),
+        }
+    }
+
+    /// # Safety
+    ///
+    /// There must be a single producer: calls to `push_back` must not
+    /// overlap, and handing the producer's role to another thread needs a
+    /// happens-before edge, e.g. joining the old producer or passing it
+    /// through a channel. One push may run alongside one
+    /// [`pop_front`](Self::pop_front).
+    pub unsafe fn push_back(&self, t: T) {
+        let new = Node::boxed(Some(t));
+        let tail = self.tail.get();
+        (&**tail).next.store(new, Ordering::Release);
+        *tail = new;
+    }
+
+    /// # Safety
+    ///
+    /// There must be a single consumer: calls to `pop_front` must not
+    /// overlap, and handing the consumer's role to another thread needs a
+    /// happens-before edge, like for the producer in
+    /// [`push_back`](Self::push_back). One pop may run alongside one push.
+    pub unsafe fn pop_front(&self) -> Option<T> {
+        self.front.pop()
+    }
+}
+
+impl<T> Default for Spsc<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    const ITER: u32 = 1000;
+
+    #[test]
+    fn spsc_in_order() {
+        let queue = Spsc::new();
+        thread::scope(|s| {
+            s.spawn(|| {
+                for i in 0..ITER {
+                    unsafe { queue.push_back(i) };
+                }
+            });
+
+            s.spawn(|| {
+                let mut expected = 0;
+                while expected < ITER {
+                    if let Some(i) = unsafe { queue.pop_front() } {
+                        assert_eq!(i, expected);
+                        expected += 1;
+                    }
+                }
+            });
+        });
+        assert_eq!(unsafe { queue.pop_front() }, None);
+    }
+
+    #[test]
+    fn mpsc_in_order_per_producer() {
+        const PRODUCERS: u32 = 4;
+        let queue = Mpsc::new();
+        thread::scope(|s| {
+            for p in 0..PRODUCERS {
+                let queue = &queue;
+                s.spawn(move || {
+                    for i in 0..ITER {
+                        queue.push_back((p, i));
+                    }
+                });
+            }
+
+            s.spawn(|| {
+                let mut next = [0; PRODUCERS as usize];
+                let mut popped = 0;
+                while popped < PRODUCERS * ITER {
+                    if let Some((p, i)) = unsafe { queue.pop_front() } {
+                        assert_eq!(i, next[p as usize]);
+                        next[p as usize] += 1;
+                        popped += 1;
+                    }
+                }
+            });
+        });
+        assert_eq!(unsafe { queue.pop_front() }, None);
+    }
+
+    #[test]
+    fn drop_frees_remaining() {
+        use crate::alloc_counter::assert_no_leak;
+
+        assert_no_leak(|| {
+            let queue = Mpsc::new();
+            for i in 0..10 {
+                queue.push_back(Box::new(i));
+            }
+            assert_eq!(unsafe { queue.pop_front() }, Some(Box::new(0)));
+        });
+
+        assert_no_leak(|| {
+            let queue = Spsc::new();
+            for i in 0..10 {
+                unsafe { queue.push_back(Box::new(i)) };
+            }
+            assert_eq!(unsafe { queue.pop_front() }, Some(Box::new(0)));
+        });
+    }
+}
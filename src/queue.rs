@@ -54,6 +54,10 @@ impl<T> Queue<T> {
         self.len.load(Ordering::Acquire)
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     #[inline]
     fn push_back_internal(
         &self,
@@ -106,7 +110,7 @@ impl<T> Queue<T> {
                         );
                     }
 
-                    let data = unsafe { ptr::read(&(*next).inner) };
+                    let data = unsafe { ptr::read(&(&*next).inner) };
                     Ok(unsafe { self.consume_and_retire(head, data) })
                 }
                 Err(_) => Err(()),
@@ -146,7 +150,13 @@ impl<T> Queue<T> {
     ) -> Option<T> {
         self.collector.retire(ptr, reclaim::boxed::<Node<T>>);
         self.len.fetch_sub(1, Ordering::Release);
-        return Some(ManuallyDrop::into_inner(data.assume_init()));
+        Some(ManuallyDrop::into_inner(data.assume_init()))
+    }
+}
+
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 